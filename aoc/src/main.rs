@@ -0,0 +1,244 @@
+//! Chores for the workspace itself, rather than any one day.
+//!
+//! `aoc new 10 "Syntax Scoring"` sets up `problems/p10-syntax-scoring` with
+//! a manifest, a `main.rs` that solves through `util::answers`, empty
+//! `input.txt` and `sample.txt`, and a sample test, ready to fill in.
+//!
+//! `aoc sample 10 page.html` takes the example from a saved puzzle page and
+//! writes it to day 10's `sample.txt`, with the example's answers in the
+//! sidecar next to it, so `--batch` can check them. Add `--force` to replace
+//! files that aren't empty.
+
+mod puzzle;
+
+use {
+  anyhow::{anyhow, Error},
+  std::{
+    env, fs,
+    path::{Path, PathBuf},
+  },
+  util::{answers::Answers, batch},
+};
+
+const MANIFEST: &str = include_str!("../templates/Cargo.toml");
+const MAIN: &str = include_str!("../templates/main.rs");
+
+/// Lowercase words joined by dashes, e.g. "Sonar Sweep!" -> "sonar-sweep".
+fn slug(title: &str) -> String {
+  title
+    .split(|c: char| !c.is_ascii_alphanumeric())
+    .filter(|word| !word.is_empty())
+    .map(str::to_ascii_lowercase)
+    .collect::<Vec<_>>()
+    .join("-")
+}
+
+/// The closest directory at or above `dir` with a workspace manifest.
+fn workspace_root(dir: &Path) -> Result<PathBuf, Error> {
+  dir
+    .ancestors()
+    .find(|dir| {
+      fs::read_to_string(dir.join("Cargo.toml")).is_ok_and(|manifest| manifest.contains("[workspace]"))
+    })
+    .map(Path::to_owned)
+    .ok_or_else(|| anyhow!("Not inside the workspace: no Cargo.toml with [workspace] above {}", dir.display()))
+}
+
+/// Makes sure the workspace's members take in `member`, a path relative to
+/// the root. The members are normally globs that already do.
+fn register(root: &Path, member: &str) -> Result<(), Error> {
+  let path = root.join("Cargo.toml");
+  let manifest = fs::read_to_string(&path)?;
+  let start = manifest
+    .find("members = [")
+    .ok_or_else(|| anyhow!("No workspace members in {}", path.display()))?;
+  let end = start + manifest[start..].find(']').ok_or_else(|| anyhow!("Unterminated members list"))?;
+
+  let listed = manifest[start + "members = [".len()..end]
+    .split(',')
+    .map(|entry| entry.trim().trim_matches(|c| c == '\'' || c == '"'))
+    .any(|entry| match entry.strip_suffix("/*") {
+      Some(dir) => Path::new(member).parent() == Some(Path::new(dir)),
+      None => entry == member,
+    });
+  if !listed {
+    let entry = format!("  '{}',\n", member);
+    fs::write(&path, format!("{}{}{}", &manifest[..end], entry, &manifest[end..]))?;
+  }
+  Ok(())
+}
+
+fn prefix(day: u32) -> String {
+  format!("p{:02}-", day)
+}
+
+/// The crate for `day`, whatever it's called, if there is one.
+fn find_day(root: &Path, day: u32) -> Result<Option<PathBuf>, Error> {
+  let Ok(entries) = fs::read_dir(root.join("problems")) else {
+    return Ok(None);
+  };
+  for entry in entries {
+    let entry = entry?;
+    if entry.file_name().to_string_lossy().starts_with(&prefix(day)) {
+      return Ok(Some(entry.path()));
+    }
+  }
+  Ok(None)
+}
+
+/// Creates the crate for `day`, returning its directory. Refuses if there's
+/// already a crate for that day, whatever it's called.
+fn new_day(root: &Path, day: u32, title: &str) -> Result<PathBuf, Error> {
+  let slug = slug(title);
+  if slug.is_empty() {
+    return Err(anyhow!("The title needs at least one letter or digit: {:?}", title));
+  }
+  if let Some(dir) = find_day(root, day)? {
+    let dir = dir.strip_prefix(root).unwrap_or(&dir).to_owned();
+    return Err(anyhow!("Day {} already exists: {}", day, dir.display()));
+  }
+
+  let name = format!("{}{}", prefix(day), slug);
+  let dir = root.join("problems").join(&name);
+  fs::create_dir_all(dir.join("src"))?;
+  fs::write(dir.join("Cargo.toml"), MANIFEST.replace("@CRATE@", &name))?;
+  fs::write(
+    dir.join("src/main.rs"),
+    MAIN.replace("@DAY@", &day.to_string()).replace("@TITLE@", title.trim()),
+  )?;
+  fs::write(dir.join("input.txt"), "")?;
+  fs::write(dir.join("sample.txt"), "")?;
+  register(root, &format!("problems/{}", name))?;
+  Ok(dir)
+}
+
+/// Writes the example from `html`, a saved puzzle page, to `day`'s
+/// `sample.txt`, and its answers to the sidecar, returning the files written.
+/// Files with anything in them are only replaced with `force`.
+fn sample(root: &Path, day: u32, html: &str, force: bool) -> Result<Vec<PathBuf>, Error> {
+  let dir = find_day(root, day)?.ok_or_else(|| anyhow!("There's no crate for day {}; make one with aoc new", day))?;
+  let parts = puzzle::parts(html);
+  let example = parts
+    .first()
+    .and_then(|part| part.example.clone())
+    .ok_or_else(|| anyhow!("Couldn't find an example in the page"))?;
+  let answers = Answers {
+    part1: parts.first().and_then(|part| part.answer.clone()),
+    part2: parts.get(1).and_then(|part| part.answer.clone()),
+  };
+
+  let sample = dir.join("sample.txt");
+  let mut files = vec![(batch::sidecar(&sample), answers.to_string()), (sample, example)];
+  if answers == Answers::default() {
+    files.remove(0);
+  }
+  for (path, _) in &files {
+    if !force && fs::metadata(path).is_ok_and(|meta| meta.len() > 0) {
+      return Err(anyhow!("{} isn't empty; use --force to replace it", path.display()));
+    }
+  }
+  for (path, contents) in &files {
+    fs::write(path, contents)?;
+  }
+  Ok(files.into_iter().map(|(path, _)| path).collect())
+}
+
+fn main() -> Result<(), Error> {
+  let args = env::args().skip(1).collect::<Vec<_>>();
+  let day = |day: &str| match day.parse::<u32>() {
+    Ok(day @ 1..=25) => Ok(day),
+    _ => Err(anyhow!("Expected a day from 1 to 25, got {:?}", day)),
+  };
+  let root = workspace_root(&env::current_dir()?)?;
+  let relative = |path: &Path| path.strip_prefix(&root).unwrap_or(path).display().to_string();
+
+  match args.iter().map(String::as_str).collect::<Vec<_>>()[..] {
+    ["new", d, ref title @ ..] if !title.is_empty() => {
+      let dir = new_day(&root, day(d)?, &title.join(" "))?;
+      println!("Created {}", relative(&dir));
+    }
+    ["sample", d, page, ref flags @ ..] if flags.iter().all(|flag| *flag == "--force") => {
+      let html = fs::read_to_string(page)?;
+      for path in sample(&root, day(d)?, &html, !flags.is_empty())? {
+        println!("Wrote {}", relative(&path));
+      }
+    }
+    _ => return Err(anyhow!("Usage: aoc new <day> <title>\n       aoc sample <day> <page.html> [--force]")),
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn slugs() {
+    assert_eq!(slug("Sonar Sweep"), "sonar-sweep");
+    assert_eq!(slug("  The Treachery of Whales!"), "the-treachery-of-whales");
+    assert_eq!(slug("?!"), "");
+  }
+
+  #[test]
+  fn new() {
+    let root = env::temp_dir().join(format!("aoc-new-{}", std::process::id()));
+    fs::create_dir_all(root.join("problems/p01-sonar-sweep")).unwrap();
+    fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\n  'util',\n  'problems/*',\n]\n").unwrap();
+
+    let dir = new_day(&root, 10, "Syntax Scoring").unwrap();
+    assert_eq!(dir, root.join("problems/p10-syntax-scoring"));
+    let manifest = fs::read_to_string(dir.join("Cargo.toml")).unwrap();
+    assert!(manifest.contains("name = \"p10-syntax-scoring\""));
+    assert!(fs::read_to_string(dir.join("src/main.rs")).unwrap().starts_with("//! Day 10: Syntax Scoring\n"));
+    assert_eq!(fs::read_to_string(dir.join("sample.txt")).unwrap(), "");
+    // Already covered by problems/*.
+    assert!(!fs::read_to_string(root.join("Cargo.toml")).unwrap().contains("p10"));
+
+    // Existing days are left alone, even under another name.
+    fs::write(dir.join("input.txt"), "1\n").unwrap();
+    assert!(new_day(&root, 10, "Something Else").is_err());
+    assert!(new_day(&root, 1, "Sonar Sweep").is_err());
+    assert_eq!(fs::read_to_string(dir.join("input.txt")).unwrap(), "1\n");
+
+    // Listed explicitly when the members don't cover it.
+    fs::write(root.join("Cargo.toml"), "[workspace]\nmembers = [\n  'util',\n]\n").unwrap();
+    new_day(&root, 11, "Dumbo Octopus").unwrap();
+    assert_eq!(
+      fs::read_to_string(root.join("Cargo.toml")).unwrap(),
+      "[workspace]\nmembers = [\n  'util',\n  'problems/p11-dumbo-octopus',\n]\n"
+    );
+
+    fs::remove_dir_all(&root).unwrap();
+  }
+
+  #[test]
+  fn samples() {
+    let root = env::temp_dir().join(format!("aoc-sample-{}", std::process::id()));
+    let dir = root.join("problems/p06-lanternfish");
+    fs::create_dir_all(&dir).unwrap();
+    fs::write(dir.join("sample.txt"), "").unwrap();
+
+    let page = |example: &str, answer: &str| {
+      format!(
+        "<article class=\"day-desc\"><pre><code>{}</code></pre><p><code><em>{}</em></code></p></article>",
+        example, answer
+      )
+    };
+    let written = sample(&root, 6, &page("3,4,3,1,2\n", "5934"), false).unwrap();
+    assert_eq!(written, vec![dir.join("sample.txt.answers"), dir.join("sample.txt")]);
+    assert_eq!(fs::read_to_string(dir.join("sample.txt")).unwrap(), "3,4,3,1,2\n");
+    assert_eq!(fs::read_to_string(dir.join("sample.txt.answers")).unwrap(), "part 1: 5934\n");
+
+    // The sidecar has answers in it now, so nothing is replaced without --force.
+    fs::write(dir.join("sample.txt"), "").unwrap();
+    assert!(sample(&root, 6, &page("1\n", "1"), false).is_err());
+    assert_eq!(fs::read_to_string(dir.join("sample.txt")).unwrap(), "");
+    sample(&root, 6, &page("1\n", "1"), true).unwrap();
+    assert_eq!(fs::read_to_string(dir.join("sample.txt.answers")).unwrap(), "part 1: 1\n");
+
+    assert!(sample(&root, 7, &page("1\n", "1"), true).is_err());
+    assert!(sample(&root, 6, "<html></html>", true).is_err());
+
+    fs::remove_dir_all(&root).unwrap();
+  }
+}
@@ -0,0 +1,121 @@
+//! Pulling the example and its answers out of a saved puzzle page.
+//!
+//! Each part of a puzzle is an `<article class="day-desc">`, and the page
+//! only has part 2's once part 1 is solved. The example input is the first
+//! `<pre><code>` block of part 1, and the example's answer is the last
+//! emphasised code (`<code><em>...</em></code>`) in each part, which is
+//! where the puzzle text states it.
+
+/// What a part's article says about its example.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Part {
+  pub example: Option<String>,
+  pub answer: Option<String>,
+}
+
+/// The text between `open` and `close` for every occurrence in `s`.
+fn between<'a>(s: &'a str, open: &'a str, close: &'a str) -> impl Iterator<Item = &'a str> {
+  let mut rest = s;
+  std::iter::from_fn(move || {
+    let start = rest.find(open)? + open.len();
+    let len = rest[start..].find(close)?;
+    let found = &rest[start..start + len];
+    rest = &rest[start + len + close.len()..];
+    Some(found)
+  })
+}
+
+/// The text of some HTML, without its tags and with entities decoded.
+fn text(html: &str) -> String {
+  let mut out = String::new();
+  let mut rest = html;
+  while let Some(start) = rest.find('<') {
+    out.push_str(&rest[..start]);
+    rest = rest[start..].find('>').map_or("", |end| &rest[start + end + 1..]);
+  }
+  out.push_str(rest);
+
+  let mut decoded = String::new();
+  let mut rest = out.as_str();
+  while let Some(start) = rest.find('&') {
+    decoded.push_str(&rest[..start]);
+    rest = &rest[start..];
+    let entity = rest.find(';').map(|end| &rest[1..end]);
+    let c = match entity {
+      Some("lt") => Some('<'),
+      Some("gt") => Some('>'),
+      Some("amp") => Some('&'),
+      Some("quot") => Some('"'),
+      Some("apos") => Some('\''),
+      Some(code) => code
+        .strip_prefix("#x")
+        .map(|hex| u32::from_str_radix(hex, 16))
+        .or_else(|| code.strip_prefix('#').map(str::parse))
+        .and_then(Result::ok)
+        .and_then(char::from_u32),
+      None => None,
+    };
+    match (c, entity) {
+      (Some(c), Some(entity)) => {
+        decoded.push(c);
+        rest = &rest[entity.len() + 2..];
+      }
+      _ => {
+        decoded.push('&');
+        rest = &rest[1..];
+      }
+    }
+  }
+  decoded.push_str(rest);
+  decoded
+}
+
+/// The parts described on the page, in order.
+pub fn parts(html: &str) -> Vec<Part> {
+  between(html, "<article class=\"day-desc\">", "</article>")
+    .map(|article| Part {
+      example: between(article, "<pre><code>", "</code></pre>").next().map(text),
+      answer: between(article, "<code><em>", "</em></code>").last().map(text),
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn page() {
+    let html = r#"<main>
+<article class="day-desc"><h2>--- Day 6: Lanternfish ---</h2>
+<p>For example, suppose you were given the following list:</p>
+<pre><code>3,4,3,1,2
+</code></pre>
+<p>Each day, a <code>0</code> becomes a <code>6</code>. In this example, after 18 days, there are a total of <code>26</code> fish. After 80 days, there would be a total of <code><em>5934</em></code>.</p>
+</article>
+<p>Your puzzle answer was <code>391888</code>.</p>
+<article class="day-desc"><h2 id="part2">--- Part Two ---</h2>
+<p>After 256 days in the example above, there would be a total of <code><em>26984457539</em></code> lanternfish!</p>
+</article>
+</main>"#;
+    assert_eq!(
+      parts(html),
+      vec![
+        Part {
+          example: Some("3,4,3,1,2\n".to_owned()),
+          answer: Some("5934".to_owned()),
+        },
+        Part {
+          example: None,
+          answer: Some("26984457539".to_owned()),
+        },
+      ]
+    );
+    assert!(parts("<html></html>").is_empty());
+  }
+
+  #[test]
+  fn entities() {
+    assert_eq!(text("<em>a</em> &lt;-&gt; b &amp;&amp; &#65;&#x42; &nope; &"), "a <-> b && AB &nope; &");
+  }
+}
@@ -0,0 +1,65 @@
+//! Day @DAY@: @TITLE@
+
+use {
+  anyhow::Error,
+  std::{env, str::FromStr},
+  tracing::{debug, info_span},
+  util::{
+    answers::{Answers, Options},
+    error::Report,
+    trace,
+  },
+};
+
+/// The puzzle input, a line at a time.
+#[derive(Debug)]
+struct Input {
+  lines: Vec<String>,
+}
+
+impl FromStr for Input {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    Ok(Input {
+      lines: s.lines().map(str::to_owned).collect(),
+    })
+  }
+}
+
+fn part1(input: &Input) -> Option<usize> {
+  debug!("{} lines", input.lines.len());
+  None
+}
+
+fn part2(_input: &Input) -> Option<usize> {
+  None
+}
+
+fn solve(input: &Input) -> Answers {
+  Answers {
+    part1: info_span!("part1").in_scope(|| part1(input)).map(|a| a.to_string()),
+    part2: info_span!("part2").in_scope(|| part2(input)).map(|a| a.to_string()),
+  }
+}
+
+fn main() -> Result<(), Report> {
+  let (options, _) = Options::from_args(env::args().skip(1))?;
+  trace::init(options.verbosity)?;
+
+  Ok(options.run(|s| {
+    let input: Input = info_span!("parse").in_scope(|| s.parse())?;
+    Ok(solve(&input))
+  })?)
+}
+
+mod tests {
+  #[test]
+  fn sample() {
+    use util::answers::Answers;
+
+    let input: crate::Input = include_str!("../sample.txt").parse().unwrap();
+    // Fill in the answers to the example once the parts are done.
+    assert_eq!(crate::solve(&input), Answers::default());
+  }
+}
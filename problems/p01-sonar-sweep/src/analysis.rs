@@ -0,0 +1,273 @@
+//! Depth analytics over a whole report: trends, moving statistics and
+//! outliers, for when counting increases isn't enough.
+
+use {
+  super::Window,
+  serde::Serialize,
+  std::fmt,
+};
+
+pub(crate) struct Options {
+  /// Width of the moving average and rolling min/max windows.
+  pub width: usize,
+  /// How many standard deviations a jump between consecutive measurements
+  /// must be from the mean jump to be reported as an outlier.
+  pub sigma: f64,
+}
+
+impl Default for Options {
+  fn default() -> Options {
+    Options {
+      width: 3,
+      sigma: 3.0,
+    }
+  }
+}
+
+#[derive(Serialize)]
+pub(crate) struct Summary {
+  pub count: usize,
+  pub min: Option<u128>,
+  pub max: Option<u128>,
+  pub mean: Option<f64>,
+  pub stddev: Option<f64>,
+}
+
+/// A stretch of consecutive measurements, starting at index `start`.
+#[derive(Serialize, Debug, PartialEq, Eq)]
+pub(crate) struct Run {
+  pub start: usize,
+  pub len: usize,
+}
+
+/// Statistics for the window of `Options::width` measurements beginning at
+/// index `start`.
+#[derive(Serialize)]
+pub(crate) struct WindowStats {
+  pub start: usize,
+  pub mean: f64,
+  pub min: u128,
+  pub max: u128,
+}
+
+/// A jump from measurement `index - 1` to measurement `index`, `z` standard
+/// deviations away from the mean jump.
+#[derive(Serialize)]
+pub(crate) struct Outlier {
+  pub index: usize,
+  pub from: u128,
+  pub to: u128,
+  pub jump: i128,
+  pub z: f64,
+}
+
+#[derive(Serialize)]
+pub(crate) struct Analysis {
+  pub summary: Summary,
+  pub longest_increasing: Option<Run>,
+  pub longest_decreasing: Option<Run>,
+  pub width: usize,
+  pub windows: Vec<WindowStats>,
+  pub sigma: f64,
+  pub outliers: Vec<Outlier>,
+}
+
+/// Returns the population mean and standard deviation, if there are any values.
+fn mean_stddev(values: &[f64]) -> Option<(f64, f64)> {
+  if values.is_empty() {
+    return None;
+  }
+  let n = values.len() as f64;
+  let mean = values.iter().sum::<f64>() / n;
+  let variance = values.iter().map(|v| (v - mean) * (v - mean)).sum::<f64>() / n;
+  Some((mean, variance.sqrt()))
+}
+
+/// Finds the first longest run where `cmp(prev, next)` holds for every pair of
+/// neighbours.
+fn longest_run(depths: &[u128], cmp: impl Fn(u128, u128) -> bool) -> Option<Run> {
+  let mut best: Option<Run> = None;
+  let mut start = 0;
+  for i in 0..depths.len() {
+    if i > 0 && !cmp(depths[i - 1], depths[i]) {
+      start = i;
+    }
+    let len = i - start + 1;
+    if best.as_ref().is_none_or(|best| len > best.len) {
+      best = Some(Run { start, len });
+    }
+  }
+  best
+}
+
+fn windows(depths: &[u128], width: usize) -> Vec<WindowStats> {
+  let mut window = Window::new(width);
+  let mut sum = 0u128;
+  let mut out = Vec::new();
+  for (i, depth) in depths.iter().enumerate() {
+    sum += depth;
+    if let Some(oldest) = window.push(*depth) {
+      sum -= oldest;
+    }
+    if window.is_full() {
+      out.push(WindowStats {
+        start: i + 1 - width,
+        mean: sum as f64 / width as f64,
+        min: window.iter().copied().min().unwrap_or_default(),
+        max: window.iter().copied().max().unwrap_or_default(),
+      });
+    }
+  }
+  out
+}
+
+fn outliers(depths: &[u128], sigma: f64) -> Vec<Outlier> {
+  let jumps = depths
+    .windows(2)
+    .map(|pair| pair[1] as i128 - pair[0] as i128)
+    .collect::<Vec<_>>();
+  let Some((mean, stddev)) = mean_stddev(&jumps.iter().map(|j| *j as f64).collect::<Vec<_>>()) else {
+    return Vec::new();
+  };
+  if stddev == 0.0 {
+    return Vec::new();
+  }
+
+  jumps
+    .iter()
+    .enumerate()
+    .filter_map(|(i, jump)| {
+      let z = (*jump as f64 - mean) / stddev;
+      (z.abs() > sigma).then_some(Outlier {
+        index: i + 1,
+        from: depths[i],
+        to: depths[i + 1],
+        jump: *jump,
+        z,
+      })
+    })
+    .collect()
+}
+
+pub(crate) fn analyse<T: Copy + Into<u128>>(depths: &[T], options: &Options) -> Analysis {
+  let depths = depths.iter().map(|d| (*d).into()).collect::<Vec<u128>>();
+  let stats = mean_stddev(&depths.iter().map(|d| *d as f64).collect::<Vec<_>>());
+
+  Analysis {
+    summary: Summary {
+      count: depths.len(),
+      min: depths.iter().copied().min(),
+      max: depths.iter().copied().max(),
+      mean: stats.map(|(mean, _)| mean),
+      stddev: stats.map(|(_, stddev)| stddev),
+    },
+    longest_increasing: longest_run(&depths, |prev, next| next > prev),
+    longest_decreasing: longest_run(&depths, |prev, next| next < prev),
+    width: options.width,
+    windows: windows(&depths, options.width),
+    sigma: options.sigma,
+    outliers: outliers(&depths, options.sigma),
+  }
+}
+
+fn or_dash<T: fmt::Display>(val: Option<T>) -> String {
+  val.map_or_else(|| "-".to_owned(), |val| val.to_string())
+}
+
+impl fmt::Display for Run {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{} measurements from index {}", self.len, self.start)
+  }
+}
+
+impl fmt::Display for Analysis {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    let summary = &self.summary;
+    writeln!(f, "{:<20} {}", "measurements", summary.count)?;
+    writeln!(f, "{:<20} {}", "min", or_dash(summary.min))?;
+    writeln!(f, "{:<20} {}", "max", or_dash(summary.max))?;
+    writeln!(f, "{:<20} {}", "mean", or_dash(summary.mean.map(|v| format!("{:.2}", v))))?;
+    writeln!(f, "{:<20} {}", "stddev", or_dash(summary.stddev.map(|v| format!("{:.2}", v))))?;
+    writeln!(f, "{:<20} {}", "longest increasing", or_dash(self.longest_increasing.as_ref()))?;
+    writeln!(f, "{:<20} {}", "longest decreasing", or_dash(self.longest_decreasing.as_ref()))?;
+
+    writeln!(f)?;
+    writeln!(f, "outliers (jumps beyond {} standard deviations):", self.sigma)?;
+    writeln!(f, "{:>7} {:>10} {:>10} {:>10} {:>8}", "index", "from", "to", "jump", "z")?;
+    for outlier in &self.outliers {
+      writeln!(
+        f,
+        "{:>7} {:>10} {:>10} {:>10} {:>8.2}",
+        outlier.index, outlier.from, outlier.to, outlier.jump, outlier.z
+      )?;
+    }
+
+    writeln!(f)?;
+    writeln!(f, "moving windows (width {}):", self.width)?;
+    writeln!(f, "{:>7} {:>10} {:>10} {:>10}", "start", "mean", "min", "max")?;
+    for window in &self.windows {
+      writeln!(
+        f,
+        "{:>7} {:>10.2} {:>10} {:>10}",
+        window.start, window.mean, window.min, window.max
+      )?;
+    }
+    Ok(())
+  }
+}
+
+mod tests {
+  #[test]
+  fn sample() {
+    use super::{analyse, Options, Run};
+
+    let depths: [u16; 10] = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+    let analysis = analyse(&depths, &Options::default());
+
+    assert_eq!(analysis.summary.count, 10);
+    assert_eq!(analysis.summary.min, Some(199));
+    assert_eq!(analysis.summary.max, Some(269));
+    assert_eq!(analysis.summary.mean, Some(225.6));
+
+    assert_eq!(analysis.longest_increasing, Some(Run { start: 0, len: 4 }));
+    assert_eq!(analysis.longest_decreasing, Some(Run { start: 3, len: 2 }));
+
+    // The same windows part 2 compares.
+    let sums = analysis
+      .windows
+      .iter()
+      .map(|w| (w.mean * 3.0).round() as u32)
+      .collect::<Vec<_>>();
+    assert_eq!(sums, vec![607, 618, 618, 617, 647, 716, 769, 792]);
+    assert_eq!((analysis.windows[3].min, analysis.windows[3].max), (200, 210));
+
+    assert!(analysis.outliers.is_empty());
+  }
+
+  #[test]
+  fn outliers() {
+    use super::{analyse, Options};
+
+    let mut depths = (0..100u32).collect::<Vec<_>>();
+    depths[50] = 1000;
+    let analysis = analyse(&depths, &Options { width: 5, sigma: 3.0 });
+
+    let found = analysis
+      .outliers
+      .iter()
+      .map(|o| (o.index, o.jump))
+      .collect::<Vec<_>>();
+    assert_eq!(found, vec![(50, 951), (51, -949)]);
+  }
+
+  #[test]
+  fn empty() {
+    use super::{analyse, Options};
+
+    let analysis = analyse::<u64>(&[], &Options::default());
+    assert_eq!(analysis.summary.count, 0);
+    assert_eq!(analysis.longest_increasing, None);
+    assert!(analysis.windows.is_empty());
+    assert!(analysis.outliers.is_empty());
+  }
+}
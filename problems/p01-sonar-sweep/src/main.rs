@@ -1,54 +1,316 @@
+mod analysis;
+
 use {
-  anyhow::Result,
+  anyhow::{anyhow, Error, Result},
+  itertools::process_results,
   std::{
-    io::{self, Read},
-    num::ParseIntError,
+    collections::VecDeque,
+    env, fmt,
+    io::{self, BufRead, Read},
     str::FromStr,
   },
+  tracing::{debug, info_span},
+  util::{
+    answers::{Answers, Format, Options},
+    error::Report,
+    parse, trace,
+  },
 };
 
-struct InputFile {
-  depths: Vec<u16>,
+/// The depth report. `T` can be any integer type; the counts below never sum
+/// depths, so wide depths can't overflow them.
+struct InputFile<T = u64> {
+  depths: Vec<T>,
 }
 
-impl FromStr for InputFile {
-  type Err = ParseIntError;
+impl<T> FromStr for InputFile<T>
+where
+  T: FromStr,
+  T::Err: fmt::Display,
+{
+  type Err = Error;
 
-  fn from_str(s: &str) -> Result<Self, ParseIntError> {
+  fn from_str(s: &str) -> Result<Self> {
     Ok(InputFile {
-      depths: s
-        .lines()
-        .map(|s| s.parse())
-        .collect::<Result<Vec<u16>, ParseIntError>>()?,
+      depths: parse::values(s)?,
     })
   }
 }
 
-fn main() -> anyhow::Result<()> {
-  // Problem 1: read the input, then count the number of times the depth
-  // increases from one measurement to the next
+/// Reads one depth per line without holding the whole input in memory.
+fn depths<T>(reader: impl BufRead) -> impl Iterator<Item = Result<T>>
+where
+  T: FromStr,
+  T::Err: fmt::Display,
+{
+  // Split by hand rather than with `lines`, to know where each line starts.
+  let mut offset = 0;
+  reader.split(b'\n').enumerate().map(move |(i, line)| {
+    let line = String::from_utf8(line?)?;
+    let start = offset;
+    offset += line.len() + 1;
+    parse::value(i + 1, start, line.strip_suffix('\r').unwrap_or(&line))
+  })
+}
+
+/// The last `k` items of a stream.
+struct Window<T> {
+  k: usize,
+  buf: VecDeque<T>,
+}
+
+impl<T> Window<T> {
+  fn new(k: usize) -> Window<T> {
+    assert!(k > 0, "window size must be at least 1");
+    Window {
+      k,
+      buf: VecDeque::with_capacity(k + 1),
+    }
+  }
+
+  /// Adds `item` to the window, returning the item that fell out of it once
+  /// the window is full.
+  fn push(&mut self, item: T) -> Option<T> {
+    self.buf.push_back(item);
+    if self.buf.len() > self.k {
+      self.buf.pop_front()
+    } else {
+      None
+    }
+  }
+
+  fn is_full(&self) -> bool {
+    self.buf.len() == self.k
+  }
+
+  fn newest(&self) -> Option<&T> {
+    self.buf.back()
+  }
+
+  fn iter(&self) -> impl Iterator<Item = &T> {
+    self.buf.iter()
+  }
+}
+
+/// Compares sliding windows of `k` consecutive depths as they arrive.
+///
+/// Two adjacent windows share all but their first and last elements, so the
+/// sum of `a[i+1..=i+k]` exceeds the sum of `a[i..i+k]` exactly when
+/// `a[i+k] > a[i]`. That means only the last `k` depths need to be kept, and
+/// no sums are ever computed, so nothing can overflow however deep it gets.
+struct WindowCompare<T> {
+  window: Window<T>,
+}
+
+impl<T: PartialOrd> WindowCompare<T> {
+  fn new(k: usize) -> WindowCompare<T> {
+    WindowCompare {
+      window: Window::new(k),
+    }
+  }
+
+  /// Returns whether the window ending at `depth` is larger than the one
+  /// before it, or `None` if there isn't a previous window yet.
+  fn push(&mut self, depth: T) -> Option<bool> {
+    let oldest = self.window.push(depth)?;
+    self.window.newest().map(|newest| *newest > oldest)
+  }
+}
+
+/// Iterator adapter yielding, for each pair of adjacent windows of size `k`,
+/// whether the depth increased. See `WindowCompare`.
+struct Increases<I: Iterator> {
+  inner: I,
+  window: WindowCompare<I::Item>,
+}
+
+impl<I> Iterator for Increases<I>
+where
+  I: Iterator,
+  I::Item: PartialOrd,
+{
+  type Item = bool;
+
+  fn next(&mut self) -> Option<bool> {
+    loop {
+      if let Some(increased) = self.window.push(self.inner.next()?) {
+        return Some(increased);
+      }
+    }
+  }
+}
+
+trait IncreasesExt: Iterator + Sized
+where
+  Self::Item: PartialOrd,
+{
+  fn increases(self, k: usize) -> Increases<Self> {
+    Increases {
+      inner: self,
+      window: WindowCompare::new(k),
+    }
+  }
+
+  fn count_increases(self, k: usize) -> usize {
+    self.increases(k).filter(|increased| *increased).count()
+  }
+}
+
+impl<I> IncreasesExt for I
+where
+  I: Iterator,
+  I::Item: PartialOrd,
+{
+}
+
+/// `p01-sonar-sweep analyse [--width N] [--sigma X]`, printing a table, or
+/// JSON with `--format json`.
+fn analyse(mut args: impl Iterator<Item = String>, format: Format) -> Result<()> {
+  let mut options = analysis::Options::default();
+  while let Some(arg) = args.next() {
+    let value = args
+      .next()
+      .ok_or_else(|| anyhow!("Missing value for {}", arg))?;
+    match arg.as_str() {
+      "--width" => {
+        options.width = match value.parse::<usize>() {
+          Ok(width) if width > 0 => width,
+          _ => return Err(anyhow!("Invalid window size: {}", value)),
+        }
+      }
+      "--sigma" => {
+        options.sigma = value
+          .parse()
+          .map_err(|_| anyhow!("Invalid number of standard deviations: {}", value))?
+      }
+      other => return Err(anyhow!("Unknown argument: {}", other)),
+    }
+  }
+
   let mut stdin = String::new();
   io::stdin().read_to_string(&mut stdin)?;
-
   let input: InputFile = stdin.parse()?;
 
-  println!(
-    "part 1: {}",
-    input
-      .depths .iter() .skip(1)
-      .zip(input.depths.iter())
-      .map(|(a, b)| -> u16 { (a > b).into() })
-      .sum::<u16>()
-  );
-
-  println!(
-    "part 2: {}",
-    input
-      .depths.windows(3).skip(1)
-      .zip(input.depths.windows(3))
-      .map(|(a, b)| -> u16 { (a.iter().sum::<u16>() > b.iter().sum()).into() })
-      .sum::<u16>()
-  );
+  let analysis = analysis::analyse(&input.depths, &options);
+  match format {
+    Format::Text => print!("{}", analysis),
+    Format::Json => println!("{}", serde_json::to_string_pretty(&analysis)?),
+  }
 
   Ok(())
 }
+
+fn solve(input: &InputFile) -> Answers {
+  debug!("{} depths", input.depths.len());
+  let part1 = info_span!("part1").in_scope(|| input.depths.iter().count_increases(1));
+  let part2 = info_span!("part2").in_scope(|| input.depths.iter().count_increases(3));
+  Answers::new(part1, part2)
+}
+
+fn main() -> Result<(), Report> {
+  // Problem 1: read the input, then count the number of times the depth
+  // increases from one measurement to the next
+  //
+  // With a window size argument, e.g. `p01-sonar-sweep 10`, stdin is instead
+  // streamed through a single window of that size. `p01-sonar-sweep analyse`
+  // prints depth analytics instead; see `analyse`.
+  let (options, args) = Options::from_args(env::args().skip(1))?;
+  trace::init(options.verbosity)?;
+  let mut args = args.into_iter();
+  match args.next() {
+    Some(cmd) if cmd == "analyse" => return Ok(analyse(args, options.format)?),
+    Some(arg) => {
+      let k = match arg.parse::<usize>() {
+        Ok(k) if k > 0 => k,
+        _ => return Err(anyhow!("Invalid window size: {}", arg).into()),
+      };
+      let count = process_results(depths::<u64>(io::stdin().lock()), |depths| depths.count_increases(k))?;
+      match options.format {
+        Format::Text => println!("window {}: {}", k, count),
+        Format::Json => println!("{}", serde_json::json!({ "window": k, "increases": count })),
+      }
+      return Ok(());
+    }
+    None => {}
+  }
+
+  Ok(options.run(|s| {
+    let input: InputFile = info_span!("parse").in_scope(|| s.parse())?;
+    Ok(solve(&input))
+  })?)
+}
+
+mod tests {
+  #[test]
+  fn sample() {
+    use {crate::IncreasesExt, util::answers::Answers};
+
+    let input: crate::InputFile = "199\n200\n208\n210\n200\n207\n240\n269\n260\n263\n"
+      .parse()
+      .unwrap();
+
+    assert_eq!(crate::solve(&input), Answers::new(7, 5));
+    assert_eq!(input.depths.iter().count_increases(10), 0);
+  }
+
+  #[test]
+  fn generated() {
+    util::generators::check(1, 100, |s| Ok(crate::solve(&s.parse()?)));
+  }
+
+  #[test]
+  fn window_sums() {
+    use crate::IncreasesExt;
+
+    // Check the shortcut against summing every window.
+    let input: crate::InputFile = include_str!("../input.txt").parse().unwrap();
+    for k in [1, 2, 3, 7, 50] {
+      let sums = input
+        .depths
+        .windows(k)
+        .map(|w| w.iter().map(|d| *d as u128).sum::<u128>())
+        .collect::<Vec<_>>();
+      let expected = sums.windows(2).filter(|w| w[1] > w[0]).count();
+      assert_eq!(input.depths.iter().count_increases(k), expected);
+    }
+  }
+
+  #[test]
+  fn streaming() {
+    use crate::IncreasesExt;
+
+    let depths = crate::depths("1\n3\n2\n4\n".as_bytes())
+      .collect::<anyhow::Result<Vec<u16>>>()
+      .unwrap();
+    assert_eq!(depths.into_iter().increases(1).collect::<Vec<_>>(), vec![true, false, true]);
+
+    let err = crate::depths::<u32>("1\nx\n".as_bytes()).nth(1).unwrap().err().unwrap();
+    assert_eq!(
+      err.to_string(),
+      "line 2, column 1: couldn't parse \"x\": invalid digit found in string"
+    );
+  }
+
+  #[test]
+  fn wide_depths() {
+    use crate::IncreasesExt;
+
+    // Too deep for a u16, which is what depths used to be parsed as.
+    let err = "100\n70000\n".parse::<crate::InputFile<u16>>().err().unwrap();
+    assert_eq!(
+      err.to_string(),
+      "line 2, column 1: couldn't parse \"70000\": number too large to fit in target type"
+    );
+
+    let input: crate::InputFile<u32> = "100\n70000\n".parse().unwrap();
+    assert_eq!(input.depths.iter().count_increases(1), 1);
+
+    // Any window of these would overflow a u64 sum.
+    let max = u64::MAX;
+    let input: crate::InputFile<u64> = format!("{}\n{}\n{}\n{}\n", max - 3, max - 1, max - 2, max)
+      .parse()
+      .unwrap();
+    assert_eq!(input.depths.iter().increases(1).collect::<Vec<_>>(), vec![true, false, true]);
+    assert_eq!(input.depths.iter().increases(3).collect::<Vec<_>>(), vec![true]);
+  }
+}
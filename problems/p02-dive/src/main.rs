@@ -1,15 +1,48 @@
+mod trajectory;
+
 use {
-  anyhow::{anyhow, Result},
+  anyhow::{anyhow, Error, Result},
   std::{
+    env, fmt,
     io::{self, Read},
+    ops::Range,
     str::FromStr,
   },
+  tracing::{debug, info_span},
+  trajectory::Trajectory,
+  util::{
+    answers::{Answers, Options},
+    error::{Diagnostic, Report},
+    trace,
+  },
 };
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Turn {
+  Left,
+  Right,
+}
+
+impl FromStr for Turn {
+  type Err = anyhow::Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s {
+      "left" => Ok(Turn::Left),
+      "right" => Ok(Turn::Right),
+      other => Err(anyhow!("Couldn't parse turn from {}", other)),
+    }
+  }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum Direction {
   Forward,
+  Back,
   Down,
   Up,
+  /// Turns by `dist` quarter turns.
+  Turn(Turn),
 }
 
 impl FromStr for Direction {
@@ -18,6 +51,7 @@ impl FromStr for Direction {
   fn from_str(s: &str) -> Result<Self, Self::Err> {
     match s {
       "forward" => Ok(Direction::Forward),
+      "back" => Ok(Direction::Back),
       "down" => Ok(Direction::Down),
       "up" => Ok(Direction::Up),
       other => Err(anyhow!("Couldn't parse direction from {}", other)),
@@ -25,6 +59,7 @@ impl FromStr for Direction {
   }
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 struct Move {
   dir: Direction,
   dist: u32,
@@ -36,60 +71,459 @@ impl FromStr for Move {
   fn from_str(s: &str) -> Result<Self, Self::Err> {
     let mut wsit = s.split_whitespace();
 
-    Ok(Move {
-      dir: wsit
-        .next()
-        .ok_or_else(|| anyhow!("No direction"))?
-        .parse()?,
-      dist: wsit.next().ok_or_else(|| anyhow!("No distance"))?.parse()?,
-    })
+    let dir = match wsit.next().ok_or_else(|| anyhow!("No direction"))? {
+      "turn" => Direction::Turn(
+        wsit
+          .next()
+          .ok_or_else(|| anyhow!("No turn direction"))?
+          .parse()?,
+      ),
+      other => other.parse()?,
+    };
+    let dist = match (dir, wsit.next()) {
+      (_, Some(dist)) => dist.parse()?,
+      (Direction::Turn(_), None) => 1,
+      (_, None) => return Err(anyhow!("No distance")),
+    };
+    if let Some(extra) = wsit.next() {
+      return Err(anyhow!("Unexpected {}", extra));
+    }
+
+    Ok(Move { dir, dist })
+  }
+}
+
+/// A line of a navigation script.
+#[derive(Debug, PartialEq, Eq)]
+enum Command {
+  /// A move, and where its line is in the input.
+  Move(Move, Range<usize>),
+  /// `repeat N { ... }`, with the body on the following lines and the closing
+  /// brace on a line of its own.
+  Repeat(usize, Vec<Command>),
+}
+
+/// The most moves a script can expand to. Nested repeats multiply, so a few
+/// lines could otherwise ask for more moves than fit in memory.
+const MAX_MOVES: usize = 10_000_000;
+
+impl Command {
+  /// How many moves this expands to, saturating rather than overflowing.
+  fn moves(&self) -> usize {
+    match self {
+      Command::Move(..) => 1,
+      Command::Repeat(n, body) => n.saturating_mul(body.iter().map(Command::moves).fold(0, usize::saturating_add)),
+    }
+  }
+
+  fn expand(&self, out: &mut Vec<Move>) {
+    match self {
+      Command::Move(mv, _) => out.push(*mv),
+      Command::Repeat(n, body) => {
+        for _ in 0..*n {
+          for cmd in body {
+            cmd.expand(out);
+          }
+        }
+      }
+    }
+  }
+}
+
+/// Where the line that makes the `i`th of the moves `commands` expand to is.
+fn span_of(commands: &[Command], mut i: usize) -> Option<Range<usize>> {
+  for cmd in commands {
+    let n = cmd.moves();
+    if i < n {
+      return match cmd {
+        Command::Move(_, span) => Some(span.clone()),
+        Command::Repeat(count, body) => span_of(body, i % (n / count)),
+      };
+    }
+    i -= n;
+  }
+  None
+}
+
+/// Parses commands up to the end of the input, or up to the `}` closing the
+/// `repeat` on line `open`. `lines` are slices of `full`, the whole input.
+/// Fails at the line that takes the block over `MAX_MOVES`.
+fn parse_block<'a>(
+  full: &str,
+  lines: &mut impl Iterator<Item = &'a str>,
+  open: Option<&'a str>,
+) -> Result<Vec<Command>> {
+  let error = |part: &str, message: &dyn fmt::Display| -> Error { Diagnostic::spanning(full, part, message).into() };
+
+  let mut commands = Vec::new();
+  let mut moves = 0usize;
+  while let Some(line) = lines.next() {
+    // Everything after a `#` is a comment.
+    let line = line.split('#').next().unwrap_or_default().trim();
+    if line.is_empty() {
+      continue;
+    }
+
+    if line == "}" {
+      return match open {
+        Some(_) => Ok(commands),
+        None => Err(error(line, &"unexpected }")),
+      };
+    }
+
+    if let Some(rest) = line.strip_prefix("repeat ") {
+      if let Some((_, body)) = rest.split_once('{').filter(|(_, body)| !body.trim().is_empty()) {
+        return Err(error(body.trim(), &"repeat body must start on the next line"));
+      }
+      let count = rest
+        .strip_suffix('{')
+        .ok_or_else(|| Error::from(Diagnostic::at(full, &line[line.len()..], "expected { after repeat count")))?
+        .trim();
+      let count = count
+        .parse()
+        .map_err(|e| error(count, &format_args!("bad repeat count: {}", e)))?;
+      commands.push(Command::Repeat(count, parse_block(full, lines, Some(line))?));
+    } else {
+      let start = line.as_ptr() as usize - full.as_ptr() as usize;
+      let mv = line.parse().map_err(|e| error(line, &e))?;
+      commands.push(Command::Move(mv, start..start + line.len()));
+    }
+
+    moves = moves.saturating_add(commands.last().unwrap().moves());
+    if moves > MAX_MOVES {
+      return Err(error(line, &format_args!("this makes more than {} moves", MAX_MOVES)));
+    }
+  }
+
+  match open {
+    Some(line) => Err(error(line, &"repeat block is never closed")),
+    None => Ok(commands),
   }
 }
 
 struct InputFile {
+  source: String,
+  commands: Vec<Command>,
   moves: Vec<Move>,
 }
 
+impl InputFile {
+  /// An error at the line that made move `i` overflow.
+  fn overflow(&self, i: usize) -> Error {
+    let span = span_of(&self.commands, i).expect("no such move");
+    Diagnostic::spanning(&self.source, &self.source[span], "this move goes out of 64-bit range").into()
+  }
+}
+
 impl FromStr for InputFile {
   type Err = anyhow::Error;
 
   fn from_str(s: &str) -> Result<Self> {
+    let commands = parse_block(s, &mut s.lines(), None)?;
+    let mut moves = Vec::new();
+    for cmd in &commands {
+      cmd.expand(&mut moves);
+    }
     Ok(InputFile {
-      moves: s.lines().map(Move::from_str).collect::<Result<Vec<Move>>>()?,
+      source: s.to_owned(),
+      commands,
+      moves,
     })
   }
 }
 
-fn main() -> Result<()> {
-  let mut stdin = String::new();
-  io::stdin().read_to_string(&mut stdin)?;
+/// Where a submarine is. All coordinates are signed, so going above the
+/// surface gives a negative depth rather than underflowing. `y` only changes
+/// after turning.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct Position {
+  x: i64,
+  y: i64,
+  depth: i64,
+}
+
+/// Which way `forward` goes in the horizontal plane, as a unit vector.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Heading {
+  dx: i64,
+  dy: i64,
+}
+
+impl Default for Heading {
+  fn default() -> Heading {
+    Heading { dx: 1, dy: 0 }
+  }
+}
 
-  let (mut x, mut y) = (0, 0);
-  let mut aim: u32;
+impl Heading {
+  fn turn(self, turn: Turn, quarters: u32) -> Heading {
+    (0..quarters % 4).fold(self, |h, _| match turn {
+      Turn::Left => Heading { dx: -h.dy, dy: h.dx },
+      Turn::Right => Heading { dx: h.dy, dy: -h.dx },
+    })
+  }
+}
 
-  let input: InputFile = stdin.parse()?;
+impl Position {
+  /// Wider than the coordinates, since it can overflow when they don't.
+  fn product(&self) -> i128 {
+    i128::from(self.x) * i128::from(self.depth)
+  }
+}
 
-  for mv in &input.moves {
+/// A navigation model: how a submarine's state changes in response to each
+/// `Move`.
+trait Submarine {
+  /// Fails, leaving the submarine as it was, if the position or aim would
+  /// overflow.
+  fn apply(&mut self, mv: &Move) -> Option<()>;
+
+  fn position(&self) -> Position;
+
+  /// Models that don't steer with aim don't have one.
+  fn aim(&self) -> Option<i64> {
+    None
+  }
+
+  /// Fails with the index of the first move that overflows.
+  fn execute(&mut self, moves: &[Move]) -> Result<(), usize> {
+    for (i, mv) in moves.iter().enumerate() {
+      self.apply(mv).ok_or(i)?;
+    }
+    Ok(())
+  }
+}
+
+/// Part 1: `up` and `down` change depth directly.
+#[derive(Debug, Default)]
+struct Simple {
+  pos: Position,
+  heading: Heading,
+}
+
+impl Submarine for Simple {
+  fn apply(&mut self, mv: &Move) -> Option<()> {
+    let dist = mv.dist as i64;
+    let mut pos = self.pos;
     match mv.dir {
-      Direction::Forward => x += mv.dist,
-      Direction::Up => y -= mv.dist,
-      Direction::Down => y += mv.dist,
+      Direction::Forward | Direction::Back => {
+        let dist = if mv.dir == Direction::Back { -dist } else { dist };
+        pos.x = pos.x.checked_add(dist * self.heading.dx)?;
+        pos.y = pos.y.checked_add(dist * self.heading.dy)?;
+      }
+      Direction::Up => pos.depth = pos.depth.checked_sub(dist)?,
+      Direction::Down => pos.depth = pos.depth.checked_add(dist)?,
+      Direction::Turn(turn) => self.heading = self.heading.turn(turn, mv.dist),
     }
+    self.pos = pos;
+    Some(())
   }
 
-  println!("Part 1: {}", x * y);
+  fn position(&self) -> Position {
+    self.pos
+  }
+}
+
+/// Part 2: `up` and `down` change aim, and `forward` moves along it. `back`
+/// retraces the same path in reverse.
+#[derive(Debug, Default)]
+struct Aimed {
+  pos: Position,
+  heading: Heading,
+  aim: i64,
+}
 
-  (x, y, aim) = (0, 0, 0);
+impl Submarine for Aimed {
+  fn aim(&self) -> Option<i64> {
+    Some(self.aim)
+  }
 
-  for mv in &input.moves {
+  fn apply(&mut self, mv: &Move) -> Option<()> {
+    let dist = mv.dist as i64;
+    let (mut pos, mut aim) = (self.pos, self.aim);
     match mv.dir {
-      Direction::Forward => { x += mv.dist; y += mv.dist * aim; },
-      Direction::Up => aim -= mv.dist,
-      Direction::Down => aim += mv.dist,
+      Direction::Forward | Direction::Back => {
+        let dist = if mv.dir == Direction::Back { -dist } else { dist };
+        pos.x = pos.x.checked_add(dist * self.heading.dx)?;
+        pos.y = pos.y.checked_add(dist * self.heading.dy)?;
+        pos.depth = pos.depth.checked_add(dist.checked_mul(aim)?)?;
+      }
+      Direction::Up => aim = aim.checked_sub(dist)?,
+      Direction::Down => aim = aim.checked_add(dist)?,
+      Direction::Turn(turn) => self.heading = self.heading.turn(turn, mv.dist),
+    }
+    (self.pos, self.aim) = (pos, aim);
+    Some(())
+  }
+
+  fn position(&self) -> Position {
+    self.pos
+  }
+}
+
+fn solve(input: &InputFile) -> Result<Answers> {
+  let part1 = info_span!("part1").in_scope(|| {
+    let mut simple = Simple::default();
+    simple.execute(&input.moves).map_err(|i| input.overflow(i))?;
+    debug!("Ended at {:?}", simple.position());
+    Ok::<_, Error>(simple.position().product())
+  })?;
+  let part2 = info_span!("part2").in_scope(|| {
+    let mut aimed = Aimed::default();
+    aimed.execute(&input.moves).map_err(|i| input.overflow(i))?;
+    debug!("Ended at {:?}", aimed.position());
+    Ok::<_, Error>(aimed.position().product())
+  })?;
+  Ok(Answers::new(part1, part2))
+}
+
+fn main() -> Result<(), Report> {
+  let (options, args) = Options::from_args(env::args().skip(1))?;
+  trace::init(options.verbosity)?;
+
+  // `p02-dive trace csv|ascii|svg` prints the path both models take instead.
+  let mut args = args.into_iter();
+  if let Some(cmd) = args.next() {
+    if cmd != "trace" {
+      return Err(anyhow!("Unknown command: {}", cmd).into());
+    }
+    let mut stdin = String::new();
+    io::stdin().read_to_string(&mut stdin)?;
+    let input: InputFile = stdin.parse()?;
+    let simple = Trajectory::record(&mut Simple::default(), &input.moves).map_err(|i| input.overflow(i))?;
+    let aimed = Trajectory::record(&mut Aimed::default(), &input.moves).map_err(|i| input.overflow(i))?;
+    let series = [("simple", &simple), ("aimed", &aimed)];
+    match args.next().as_deref() {
+      Some("csv") => print!("{}", trajectory::csv(&series)),
+      Some("ascii") => print!("{}", trajectory::ascii(&series, 72, 20)),
+      Some("svg") => print!("{}", trajectory::svg(&series, 800, 400)),
+      other => return Err(anyhow!("Expected csv, ascii or svg, got {:?}", other).into()),
     }
+    return Ok(());
+  }
+
+  Ok(options.run(|s| {
+    let input: InputFile = info_span!("parse").in_scope(|| s.parse())?;
+    solve(&input)
+  })?)
+}
+
+mod tests {
+  #[test]
+  fn sample() {
+    use crate::{Aimed, InputFile, Position, Simple, Submarine};
+
+    let input: InputFile = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n"
+      .parse()
+      .unwrap();
+
+    let mut sub = Simple::default();
+    sub.execute(&input.moves).unwrap();
+    assert_eq!(sub.position(), Position { x: 15, y: 0, depth: 10 });
+
+    let mut sub = Aimed::default();
+    sub.execute(&input.moves).unwrap();
+    assert_eq!(sub.position(), Position { x: 15, y: 0, depth: 60 });
+    assert_eq!(sub.position().product(), 900);
+
+    assert_eq!(crate::solve(&input).unwrap(), util::answers::Answers::new(150, 900));
+  }
+
+  #[test]
+  fn generated() {
+    util::generators::check(2, 100, |s| crate::solve(&s.parse()?));
+  }
+
+  #[test]
+  fn above_surface() {
+    use crate::{Aimed, InputFile, Position, Simple, Submarine};
+
+    let input: InputFile = "up 3\nforward 2\n".parse().unwrap();
+
+    let mut sub = Simple::default();
+    sub.execute(&input.moves).unwrap();
+    assert_eq!(sub.position(), Position { x: 2, y: 0, depth: -3 });
+
+    let mut sub = Aimed::default();
+    sub.execute(&input.moves).unwrap();
+    assert_eq!(sub.position(), Position { x: 2, y: 0, depth: -6 });
+  }
+
+  #[test]
+  fn script() {
+    use crate::{Aimed, InputFile, Position, Simple, Submarine};
+
+    let input: InputFile = "\
+# Two sides of a spiral, then reverse.
+down 1
+
+repeat 2 {
+  forward 3   # one side
+  turn left
+  repeat 2 {
+    forward 1
+  }
+}
+turn right 2
+back 4
+"
+    .parse()
+    .unwrap();
+    assert_eq!(input.moves.len(), 11);
+
+    let mut sub = Simple::default();
+    sub.execute(&input.moves).unwrap();
+    // (3, 0) -> (3, 2) -> (3, 5) -> (1, 5), then facing +x and backing up 4
+    assert_eq!(sub.position(), Position { x: -3, y: 5, depth: 1 });
+
+    let mut sub = Aimed::default();
+    sub.execute(&input.moves).unwrap();
+    // 10 forward and 4 back, all with an aim of 1
+    assert_eq!(sub.position(), Position { x: -3, y: 5, depth: 6 });
+  }
+
+  #[test]
+  fn script_errors() {
+    use crate::InputFile;
+
+    let err = |s: &str| s.parse::<InputFile>().err().unwrap().to_string();
+
+    assert_eq!(err("forward 1\nsideways 2\n"), "line 2, column 1: Couldn't parse direction from sideways");
+    assert_eq!(err("forward\n"), "line 1, column 1: No distance");
+    assert_eq!(err("turn up\n"), "line 1, column 1: Couldn't parse turn from up");
+    assert_eq!(err("forward 1 2\n"), "line 1, column 1: Unexpected 2");
+    assert_eq!(err("# start\nrepeat 2 {\n  up 1\n"), "line 2, column 1: repeat block is never closed");
+    assert_eq!(err("up 1\n  }\n"), "line 2, column 3: unexpected }");
+    assert_eq!(err("repeat x {\n}\n"), "line 1, column 8: bad repeat count: invalid digit found in string");
+    assert_eq!(err("repeat 2\n"), "line 1, column 9: expected { after repeat count");
+    assert_eq!(err("repeat 3 { forward 1 }\n"), "line 1, column 12: repeat body must start on the next line");
+
+    // Caught before expanding anything, at the repeat that goes over.
+    let nested = "forward 1\nrepeat 100000 {\n  repeat 100000 {\n    forward 1\n  }\n}\n";
+    assert_eq!(err(nested), "line 2, column 1: this makes more than 10000000 moves");
+    let total = "repeat 9000000 {\n  up 1\n}\nrepeat 2000000 {\n  down 1\n}\n";
+    assert_eq!(err(total), "line 4, column 1: this makes more than 10000000 moves");
+    let huge = format!("repeat {} {{\n  repeat 2 {{\n    up 1\n  }}\n}}\n", usize::MAX);
+    assert_eq!(err(&huge), "line 1, column 1: this makes more than 10000000 moves");
   }
 
-  println!("Part 2: {}", x * y);
+  #[test]
+  fn overflow() {
+    use crate::{InputFile, Simple, Submarine};
 
-  Ok(())
+    let err = |s: &str| crate::solve(&s.parse::<InputFile>().unwrap()).err().unwrap().to_string();
+
+    // Part 1's product doesn't fit in an i64 either, but only part 2 fails.
+    let input = "down 4294967295\ndown 4294967295\nforward 4294967295\n";
+    let mut sub = Simple::default();
+    sub.execute(&input.parse::<InputFile>().unwrap().moves).unwrap();
+    assert_eq!(sub.position().product(), 36893488130239234050);
+    assert_eq!(err(input), "line 3, column 1: this move goes out of 64-bit range");
+
+    // The tenth time round, pointing at the line inside the repeat.
+    let repeated = "down 1000000000\nrepeat 10 {\n  forward 1000000000\n}\n";
+    assert_eq!(err(repeated), "line 3, column 3: this move goes out of 64-bit range");
+    let body = "down 1000000000\nrepeat 10 {\n  turn left 4\n  forward 1000000000\n}\n";
+    assert_eq!(err(body), "line 4, column 3: this move goes out of 64-bit range");
+  }
 }
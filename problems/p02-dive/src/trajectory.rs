@@ -0,0 +1,253 @@
+//! Recording where a submarine goes, and showing it as CSV or a depth
+//! profile, to compare navigation models side by side.
+
+use {
+  super::{Move, Position, Submarine},
+  std::fmt::Write,
+};
+
+/// A submarine's state after some number of moves.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) struct Sample {
+  pub pos: Position,
+  /// Only models that steer with aim have one.
+  pub aim: Option<i64>,
+}
+
+/// The state before any moves, followed by the state after each move.
+pub(crate) struct Trajectory {
+  pub samples: Vec<Sample>,
+}
+
+impl Trajectory {
+  /// Fails with the index of the first move that overflows, like
+  /// `Submarine::execute`.
+  pub fn record(sub: &mut impl Submarine, moves: &[Move]) -> Result<Trajectory, usize> {
+    let sample = |sub: &dyn Submarine| Sample {
+      pos: sub.position(),
+      aim: sub.aim(),
+    };
+    let mut samples = vec![sample(sub)];
+    for (i, mv) in moves.iter().enumerate() {
+      sub.apply(mv).ok_or(i)?;
+      samples.push(sample(sub));
+    }
+    Ok(Trajectory { samples })
+  }
+
+  fn depths(&self) -> impl Iterator<Item = i64> + '_ {
+    self.samples.iter().map(|s| s.pos.depth)
+  }
+}
+
+/// A labelled trajectory. The first character of the label marks it in ASCII
+/// plots.
+pub(crate) type Series<'a> = (&'a str, &'a Trajectory);
+
+/// One row per model per step: `model,step,x,y,depth,aim`, with an empty
+/// `aim` for models that don't have one.
+pub(crate) fn csv(series: &[Series]) -> String {
+  let mut out = String::from("model,step,x,y,depth,aim\n");
+  for (name, trajectory) in series {
+    for (step, sample) in trajectory.samples.iter().enumerate() {
+      let aim = sample.aim.map(|aim| aim.to_string()).unwrap_or_default();
+      let Position { x, y, depth } = sample.pos;
+      writeln!(out, "{},{},{},{},{},{}", name, step, x, y, depth, aim).unwrap();
+    }
+  }
+  out
+}
+
+/// The number of steps and the range of depths covered by all the series.
+fn bounds(series: &[Series]) -> (usize, i64, i64) {
+  let steps = series
+    .iter()
+    .map(|(_, t)| t.samples.len())
+    .max()
+    .unwrap_or(0);
+  let depths = || series.iter().flat_map(|(_, t)| t.depths());
+  let min = depths().min().unwrap_or(0);
+  let max = depths().max().unwrap_or(0);
+  (steps, min, max)
+}
+
+/// Scales `val` from `0..=range` onto `0..=len - 1`.
+fn scale(val: i128, range: i128, len: usize) -> usize {
+  if range == 0 {
+    0
+  } else {
+    (val * (len as i128 - 1) / range) as usize
+  }
+}
+
+/// Plots depth (downwards) against step (rightwards) on a `width` by `height`
+/// grid of characters. Where series overlap the cell shows `*`.
+pub(crate) fn ascii(series: &[Series], width: usize, height: usize) -> String {
+  assert!(width > 0 && height > 0);
+  let (steps, min, max) = bounds(series);
+  let mut grid = vec![vec![' '; width]; height];
+
+  for (name, trajectory) in series {
+    let marker = name.chars().next().unwrap_or('?');
+    let cols = (0..width).map(|col| scale(col as i128, width as i128 - 1, steps.max(1)));
+    for (col, step) in cols.enumerate() {
+      let Some(sample) = trajectory.samples.get(step) else {
+        continue;
+      };
+      let row = scale(i128::from(sample.pos.depth) - i128::from(min), i128::from(max) - i128::from(min), height);
+      let cell = &mut grid[row][col];
+      *cell = if *cell == ' ' || *cell == marker { marker } else { '*' };
+    }
+  }
+
+  let label_width = min.to_string().len().max(max.to_string().len());
+  let mut out = String::new();
+  for (row, cells) in grid.iter().enumerate() {
+    let label = match row {
+      0 => min.to_string(),
+      _ if row == height - 1 => max.to_string(),
+      _ => String::new(),
+    };
+    writeln!(out, "{:>w$} |{}", label, cells.iter().collect::<String>(), w = label_width).unwrap();
+  }
+  writeln!(out, "{:>w$} +{}", "", "-".repeat(width), w = label_width).unwrap();
+  let legend = series
+    .iter()
+    .map(|(name, _)| format!("{} = {}", name.chars().next().unwrap_or('?'), name))
+    .collect::<Vec<_>>()
+    .join(", ");
+  writeln!(out, "{:>w$}  step 0..{}; {}", "", steps.saturating_sub(1), legend, w = label_width).unwrap();
+  out
+}
+
+/// The same plot as `ascii`, as an SVG document `width` by `height` pixels.
+pub(crate) fn svg(series: &[Series], width: u32, height: u32) -> String {
+  const MARGIN: f64 = 40.0;
+  const COLOURS: [&str; 4] = ["#1f77b4", "#d62728", "#2ca02c", "#9467bd"];
+
+  let (steps, min, max) = bounds(series);
+  let (plot_w, plot_h) = (width as f64 - 2.0 * MARGIN, height as f64 - 2.0 * MARGIN);
+  let x = |step: usize| MARGIN + plot_w * step as f64 / (steps.max(2) - 1) as f64;
+  let y = |depth: i64| MARGIN + plot_h * (depth as f64 - min as f64) / (max as f64 - min as f64).max(1.0);
+
+  let mut out = String::new();
+  writeln!(
+    out,
+    r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w}" height="{h}" viewBox="0 0 {w} {h}">"#,
+    w = width,
+    h = height
+  )
+  .unwrap();
+  writeln!(out, r#"  <rect width="100%" height="100%" fill="white"/>"#).unwrap();
+  writeln!(
+    out,
+    r#"  <path d="M {l} {t} V {b} H {r}" fill="none" stroke="black"/>"#,
+    l = MARGIN,
+    t = MARGIN,
+    b = MARGIN + plot_h,
+    r = MARGIN + plot_w
+  )
+  .unwrap();
+  writeln!(out, r#"  <text x="4" y="{}" font-size="10">{}</text>"#, MARGIN, min).unwrap();
+  writeln!(out, r#"  <text x="4" y="{}" font-size="10">{}</text>"#, MARGIN + plot_h, max).unwrap();
+
+  for (i, (name, trajectory)) in series.iter().enumerate() {
+    let colour = COLOURS[i % COLOURS.len()];
+    let points = trajectory
+      .depths()
+      .enumerate()
+      .map(|(step, depth)| format!("{:.1},{:.1}", x(step), y(depth)))
+      .collect::<Vec<_>>()
+      .join(" ");
+    writeln!(
+      out,
+      r#"  <polyline fill="none" stroke="{}" points="{}"/>"#,
+      colour, points
+    )
+    .unwrap();
+    writeln!(
+      out,
+      r#"  <text x="{}" y="{}" font-size="12" fill="{}">{}</text>"#,
+      MARGIN + 8.0,
+      MARGIN + 16.0 * (i + 1) as f64,
+      colour,
+      name
+    )
+    .unwrap();
+  }
+
+  out.push_str("</svg>\n");
+  out
+}
+
+mod tests {
+  #[test]
+  fn record() {
+    use {
+      super::{Sample, Trajectory},
+      crate::{Aimed, InputFile, Position, Submarine},
+    };
+
+    let input: InputFile = "down 2\nforward 3\nup 1\n".parse().unwrap();
+    let mut sub = Aimed::default();
+    let trajectory = Trajectory::record(&mut sub, &input.moves).unwrap();
+
+    let at = |x, depth, aim| Sample {
+      pos: Position { x, y: 0, depth },
+      aim: Some(aim),
+    };
+    assert_eq!(
+      trajectory.samples,
+      vec![at(0, 0, 0), at(0, 0, 2), at(3, 6, 2), at(3, 6, 1)]
+    );
+    assert_eq!(trajectory.samples.last().unwrap().pos, sub.position());
+  }
+
+  #[test]
+  fn csv() {
+    use {
+      super::{csv, Trajectory},
+      crate::{Aimed, InputFile, Simple},
+    };
+
+    let input: InputFile = "down 2\nforward 3\n".parse().unwrap();
+    let simple = Trajectory::record(&mut Simple::default(), &input.moves).unwrap();
+    let aimed = Trajectory::record(&mut Aimed::default(), &input.moves).unwrap();
+
+    assert_eq!(
+      csv(&[("simple", &simple), ("aimed", &aimed)]),
+      "model,step,x,y,depth,aim\n\
+       simple,0,0,0,0,\n\
+       simple,1,0,0,2,\n\
+       simple,2,3,0,2,\n\
+       aimed,0,0,0,0,0\n\
+       aimed,1,0,0,0,2\n\
+       aimed,2,3,0,6,2\n"
+    );
+  }
+
+  #[test]
+  fn plots() {
+    use {
+      super::{ascii, svg, Trajectory},
+      crate::{Aimed, InputFile, Simple},
+    };
+
+    let input: InputFile = "forward 5\ndown 5\nforward 8\nup 3\ndown 8\nforward 2\n"
+      .parse()
+      .unwrap();
+    let simple = Trajectory::record(&mut Simple::default(), &input.moves).unwrap();
+    let aimed = Trajectory::record(&mut Aimed::default(), &input.moves).unwrap();
+    let series = [("simple", &simple), ("aimed", &aimed)];
+
+    assert_eq!(
+      ascii(&series, 7, 4),
+      " 0 |***ssss\n   |       \n   |   aaa \n60 |      a\n   +-------\n    step 0..6; s = simple, a = aimed\n"
+    );
+
+    let svg = svg(&series, 400, 300);
+    assert!(svg.starts_with("<svg "));
+    assert!(svg.ends_with("</svg>\n"));
+    assert_eq!(svg.matches("<polyline").count(), 2);
+  }
+}
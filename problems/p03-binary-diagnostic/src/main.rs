@@ -1,70 +1,619 @@
 use {
   anyhow::{anyhow, Error},
-  std::{
-    io::{self, Read},
-    str::FromStr,
+  bits::Bits,
+  trie::{Criterion, Trie},
+  std::{env, str::FromStr},
+  tracing::{debug, info, info_span},
+  util::{
+    answers::{Answers, Options},
+    error, parse, trace,
   },
 };
 
-const BITS: usize = 12;
-
-fn main() -> Result<(), Error> {
-  let input = io::stdin()
-    .lines()
-    .map(|l| {
-      l.map_err(Error::from)
-        .and_then(|s| u64::from_str_radix(&s, 2).map_err(Error::from))
-    })
-    .collect::<Result<Vec<u64>, Error>>()?;
-  
-  // Part 1
-  {
-    // there's probably a better way to do this.
-    let mut gamma: u64 = 0;
-    for d in 0..BITS {
-      let set = input
-        .iter()
-        .map(|i| (*i as usize & 1 << d) >> d)
-        .sum::<usize>()
-        > input.len() / 2;
-      let bit: u64 = set.into();
-
-      gamma |= bit << d;
-    }
-
-    let epsilon = !gamma & ((1 << BITS) - 1);
-
-    dbg!(gamma);
-    dbg!(epsilon);
-    dbg!(gamma * epsilon);
-  }
-
-  // Part 2
-  let p2 = |pred: fn(&[usize], &[usize]) -> bool| {
-    let mut q = (0..input.len()).collect::<Vec<usize>>();
-    for d in (0..BITS).rev() {
-      if q.len() == 1 { break; }
-      let mut set_idxs = Vec::<usize>::new();
-      let mut unset_idxs = Vec::<usize>::new();
-      for (idx, set) in q
-        .into_iter()
-        .map(|idx| (idx, (input[idx] & 1 << d) != 0))
-        {
-          if set { set_idxs.push(idx); }
-          else { unset_idxs.push(idx); }
+mod bits {
+  use std::{fmt, ops};
+
+  /// A fixed-width bit vector, used so that report lines aren't limited to
+  /// the width of a `u64`.
+  ///
+  /// Bit 0 is the least significant bit, i.e. the last character of the line
+  /// it was parsed from. Bits above `len` in the last word are always zero.
+  #[derive(Clone, PartialEq, Eq, Default)]
+  pub(crate) struct Bits {
+    words: Vec<u64>,
+    len: usize,
+  }
+
+  impl Bits {
+    pub fn zeros(len: usize) -> Bits {
+      Bits {
+        words: vec![0; len.div_ceil(64)],
+        len,
+      }
+    }
+
+    pub fn width(&self) -> usize {
+      self.len
+    }
+
+    pub fn get(&self, d: usize) -> bool {
+      assert!(d < self.len, "bit {} out of range for width {}", d, self.len);
+      self.words[d / 64] & 1 << (d % 64) != 0
+    }
+
+    pub fn set(&mut self, d: usize, val: bool) {
+      assert!(d < self.len, "bit {} out of range for width {}", d, self.len);
+      if val {
+        self.words[d / 64] |= 1 << (d % 64);
+      } else {
+        self.words[d / 64] &= !(1 << (d % 64));
+      }
+    }
+
+    fn is_zero(&self) -> bool {
+      self.words.iter().all(|w| *w == 0)
+    }
+
+    /// Divides in place by `rhs`, returning the remainder.
+    fn div_rem_small(&mut self, rhs: u64) -> u64 {
+      let mut rem = 0u128;
+      for word in self.words.iter_mut().rev() {
+        let cur = rem << 64 | *word as u128;
+        *word = (cur / rhs as u128) as u64;
+        rem = cur % rhs as u128;
+      }
+      rem as u64
+    }
+  }
+
+  impl ops::Not for &Bits {
+    type Output = Bits;
+
+    fn not(self) -> Bits {
+      let mut out = Bits {
+        words: self.words.iter().map(|w| !w).collect(),
+        len: self.len,
+      };
+      if !self.len.is_multiple_of(64) {
+        if let Some(last) = out.words.last_mut() {
+          *last &= (1 << (self.len % 64)) - 1;
         }
-      match pred(&set_idxs, &unset_idxs) {
-        true => q = set_idxs,
-        false => q = unset_idxs
       }
+      out
+    }
+  }
+
+  impl ops::Mul for &Bits {
+    type Output = Bits;
+
+    /// Schoolbook multiplication; the result is wide enough to never overflow.
+    fn mul(self, rhs: &Bits) -> Bits {
+      let mut out = Bits::zeros(self.len + rhs.len);
+      for (i, x) in self.words.iter().enumerate() {
+        let mut carry = 0u128;
+        for (j, y) in rhs.words.iter().enumerate() {
+          let cur = out.words[i + j] as u128 + *x as u128 * *y as u128 + carry;
+          out.words[i + j] = cur as u64;
+          carry = cur >> 64;
+        }
+        if carry != 0 {
+          out.words[i + rhs.words.len()] = carry as u64;
+        }
+      }
+      out
+    }
+  }
+
+  /// Builds a value from its bits, most significant first.
+  impl FromIterator<bool> for Bits {
+    fn from_iter<I: IntoIterator<Item = bool>>(iter: I) -> Self {
+      let bits = iter.into_iter().collect::<Vec<_>>();
+      let mut out = Bits::zeros(bits.len());
+      for (i, bit) in bits.into_iter().rev().enumerate() {
+        out.set(i, bit);
+      }
+      out
+    }
+  }
+
+  /// Formats as a decimal number, the same as the `u64`s this replaces.
+  impl fmt::Display for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      const CHUNK: u64 = 10_000_000_000_000_000_000;
+
+      let mut rest = self.clone();
+      let mut chunks = Vec::new();
+      loop {
+        chunks.push(rest.div_rem_small(CHUNK));
+        if rest.is_zero() {
+          break;
+        }
+      }
+
+      let mut chunks = chunks.iter().rev();
+      if let Some(first) = chunks.next() {
+        write!(f, "{}", first)?;
+      }
+      for chunk in chunks {
+        write!(f, "{:019}", chunk)?;
+      }
+      Ok(())
     }
-    dbg!(input[*q.first().unwrap()])
+  }
+
+  impl fmt::Debug for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      fmt::Display::fmt(self, f)
+    }
+  }
+
+  impl fmt::Binary for Bits {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      for d in (0..self.len).rev() {
+        write!(f, "{}", if self.get(d) { '1' } else { '0' })?;
+      }
+      Ok(())
+    }
+  }
+} // mod bits
+
+/// The diagnostic report. Every line has the same number of bits, which is
+/// taken from the first line.
+struct Report {
+  width: usize,
+  values: Vec<Bits>,
+}
+
+impl FromStr for Report {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let values = parse::binary(s)?
+      .into_iter()
+      .map(Bits::from_iter)
+      .collect::<Vec<_>>();
+    let width = values
+      .first()
+      .ok_or_else(|| anyhow!("Empty report"))?
+      .width();
+    Ok(Report { width, values })
+  }
+}
+
+impl Report {
+  fn trie(&self) -> Trie {
+    Trie::new(self.width, &self.values)
+  }
+}
+
+mod trie {
+  use super::{stats::BitStats, Bits};
+
+  #[derive(Default)]
+  struct Node {
+    /// Number of report lines that pass through this node.
+    count: usize,
+    /// Indices into `Trie::nodes`, for a `0` and a `1` bit respectively.
+    children: [Option<usize>; 2],
+  }
+
+  /// Which bit to follow when filtering the report down to a single line.
+  #[derive(Clone, Copy)]
+  pub(crate) enum Keep {
+    /// The most common bit at each position.
+    Majority,
+    /// The least common bit at each position.
+    Minority,
+  }
+
+  /// A filtering policy: which bit to keep, and which bit wins a tie.
+  #[derive(Clone, Copy)]
+  pub(crate) struct Criterion {
+    pub keep: Keep,
+    pub tie: bool,
+  }
+
+  impl Criterion {
+    /// Keep the most common bit, preferring `1` on a tie.
+    pub const MOST_COMMON: Criterion = Criterion {
+      keep: Keep::Majority,
+      tie: true,
+    };
+
+    /// Keep the least common bit, preferring `0` on a tie.
+    pub const LEAST_COMMON: Criterion = Criterion {
+      keep: Keep::Minority,
+      tie: false,
+    };
+
+    /// Returns whether to follow the `1` branch, given the number of lines with
+    /// the bit set and unset.
+    pub fn choose(&self, set: usize, unset: usize) -> bool {
+      match self.keep {
+        _ if set == unset => self.tie,
+        Keep::Majority => set > unset,
+        Keep::Minority => set < unset,
+      }
+    }
+  }
+
+  /// A binary trie over the lines of the report, most significant bit first.
+  ///
+  /// Each node counts the lines below it, so bit statistics and ratings can be
+  /// read off by walking the trie instead of re-partitioning the report.
+  pub(crate) struct Trie {
+    width: usize,
+    nodes: Vec<Node>,
+  }
+
+  impl Trie {
+    pub fn new<'a>(width: usize, values: impl IntoIterator<Item = &'a Bits>) -> Trie {
+      let mut trie = Trie {
+        width,
+        nodes: vec![Node::default()],
+      };
+      for value in values {
+        let mut idx = 0;
+        trie.nodes[idx].count += 1;
+        for d in (0..width).rev() {
+          let bit = value.get(d) as usize;
+          idx = match trie.nodes[idx].children[bit] {
+            Some(child) => child,
+            None => {
+              trie.nodes.push(Node::default());
+              let child = trie.nodes.len() - 1;
+              trie.nodes[idx].children[bit] = Some(child);
+              child
+            }
+          };
+          trie.nodes[idx].count += 1;
+        }
+      }
+      trie
+    }
+
+    /// Number of lines in the trie.
+    pub fn len(&self) -> usize {
+      self.nodes[0].count
+    }
+
+    fn count(&self, idx: Option<usize>) -> usize {
+      idx.map_or(0, |idx| self.nodes[idx].count)
+    }
+
+    pub fn stats(&self) -> BitStats {
+      BitStats::new(self.len(), self.ones())
+    }
+
+    /// Returns the number of lines with bit `d` set, for each `d` in `0..width`.
+    fn ones(&self) -> Vec<usize> {
+      let mut ones = vec![0; self.width];
+      let mut level = vec![0];
+      for d in (0..self.width).rev() {
+        let mut next = Vec::new();
+        for idx in level {
+          let [zero, one] = self.nodes[idx].children;
+          ones[d] += self.count(one);
+          next.extend(zero.into_iter().chain(one));
+        }
+        level = next;
+      }
+      ones
+    }
+
+    /// Walks the trie from the most significant bit, using `pred(set, unset)`
+    /// to decide whether to follow the `1` branch. Empty branches are never
+    /// followed, so this stops narrowing once a single line remains.
+    pub fn filter(&self, pred: impl Fn(usize, usize) -> bool) -> Bits {
+      let mut out = Bits::zeros(self.width);
+      let mut idx = 0;
+      for d in (0..self.width).rev() {
+        let [zero, one] = self.nodes[idx].children;
+        let bit = match (zero, one) {
+          (Some(_), None) => false,
+          (None, Some(_)) => true,
+          _ => pred(self.count(one), self.count(zero)),
+        };
+        out.set(d, bit);
+        idx = self.nodes[idx].children[bit as usize].expect("non-empty trie");
+      }
+      out
+    }
+
+    pub fn filter_by(&self, criterion: Criterion) -> Bits {
+      self.filter(|set, unset| criterion.choose(set, unset))
+    }
+  }
+} // mod trie
+
+mod stats {
+  use {
+    super::{trie::Criterion, Bits},
+    std::fmt,
   };
 
-  dbg!(
-    p2(|set, unset| set.len() >= unset.len()) *
-    p2(|set, unset| unset.len() > set.len())
-  );
+  /// Per-position counts of set and unset bits across the report.
+  ///
+  /// Positions are numbered like `Bits`, so position 0 is the last column.
+  pub(crate) struct BitStats {
+    len: usize,
+    ones: Vec<usize>,
+  }
+
+  impl BitStats {
+    pub fn new(len: usize, ones: Vec<usize>) -> BitStats {
+      assert!(ones.iter().all(|n| *n <= len));
+      BitStats { len, ones }
+    }
+
+    pub fn width(&self) -> usize {
+      self.ones.len()
+    }
+
+    pub fn ones(&self, d: usize) -> usize {
+      self.ones[d]
+    }
+
+    pub fn zeros(&self, d: usize) -> usize {
+      self.len - self.ones[d]
+    }
+
+    /// Picks the bit at each position according to `criterion`, which also
+    /// decides what happens when a position has as many ones as zeros.
+    pub fn select(&self, criterion: Criterion) -> Bits {
+      let mut out = Bits::zeros(self.width());
+      for d in 0..self.width() {
+        out.set(d, criterion.choose(self.ones(d), self.zeros(d)));
+      }
+      out
+    }
+
+    pub fn gamma(&self) -> Bits {
+      self.select(Criterion::MOST_COMMON)
+    }
+
+    pub fn epsilon(&self) -> Bits {
+      self.select(Criterion::LEAST_COMMON)
+    }
+  }
+
+  /// A table of the counts at each position, most significant first, with
+  /// ties flagged.
+  impl fmt::Display for BitStats {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      let (gamma, epsilon) = (self.gamma(), self.epsilon());
+      writeln!(f, "{:>5} {:>7} {:>7} {:>5} {:>7}", "bit", "ones", "zeros", "gamma", "epsilon")?;
+      for d in (0..self.width()).rev() {
+        write!(
+          f,
+          "{:>5} {:>7} {:>7} {:>5} {:>7}",
+          d,
+          self.ones(d),
+          self.zeros(d),
+          gamma.get(d) as u8,
+          epsilon.get(d) as u8
+        )?;
+        if self.ones(d) == self.zeros(d) {
+          write!(f, "  (tie)")?;
+        }
+        writeln!(f)?;
+      }
+      Ok(())
+    }
+  }
+} // mod stats
+
+fn oxygen(trie: &Trie) -> Bits {
+  trie.filter_by(Criterion::MOST_COMMON)
+}
+
+fn co2(trie: &Trie) -> Bits {
+  trie.filter_by(Criterion::LEAST_COMMON)
+}
+
+fn solve(report: &Report) -> Answers {
+  let trie = report.trie();
+  let part1 = info_span!("part1").in_scope(|| {
+    let stats = trie.stats();
+    debug!("Bit counts:\n{}", stats);
+    let (gamma, epsilon) = (stats.gamma(), stats.epsilon());
+    info!("gamma {}, epsilon {}", gamma, epsilon);
+    &gamma * &epsilon
+  });
+  let part2 = info_span!("part2").in_scope(|| {
+    let (oxygen, co2) = (oxygen(&trie), co2(&trie));
+    info!("oxygen {}, co2 {}", oxygen, co2);
+    &oxygen * &co2
+  });
+  Answers::new(part1, part2)
+}
+
+fn main() -> Result<(), error::Report> {
+  let (options, _) = Options::from_args(env::args().skip(1))?;
+  trace::init(options.verbosity)?;
+
+  Ok(options.run(|input| {
+    let report: Report = info_span!("parse").in_scope(|| input.parse())?;
+    Ok(solve(&report))
+  })?)
+}
+
+#[cfg(test)]
+mod tests {
+  use proptest::prelude::*;
+
+  #[test]
+  fn sample() {
+    let report: crate::Report = include_str!("../sample.txt").parse().unwrap();
+    assert_eq!(report.width, 5);
+    let trie = report.trie();
+
+    let stats = trie.stats();
+    let (gamma, epsilon) = (stats.gamma(), stats.epsilon());
+    assert_eq!(gamma.to_string(), "22");
+    assert_eq!(epsilon.to_string(), "9");
+    assert_eq!((&gamma * &epsilon).to_string(), "198");
 
-  Ok(())
-}
\ No newline at end of file
+    assert_eq!(crate::oxygen(&trie).to_string(), "23");
+    assert_eq!(crate::co2(&trie).to_string(), "10");
+
+    assert_eq!(crate::solve(&report), util::answers::Answers::new(198, 230));
+  }
+
+  #[test]
+  fn generated() {
+    util::generators::check(3, 100, |s| Ok(crate::solve(&s.parse()?)));
+  }
+
+  #[test]
+  fn input() {
+    let report: crate::Report = include_str!("../input.txt").parse().unwrap();
+    assert_eq!(report.width, 12);
+    let trie = report.trie();
+
+    let stats = trie.stats();
+    let (gamma, epsilon) = (stats.gamma(), stats.epsilon());
+    assert_eq!((&gamma * &epsilon).to_string(), "4001724");
+    assert_eq!((&crate::oxygen(&trie) * &crate::co2(&trie)).to_string(), "587895");
+  }
+
+  #[test]
+  fn trie() {
+    use crate::trie::{Criterion, Keep};
+
+    let report: crate::Report = "101\n101\n100\n011\n".parse().unwrap();
+    let trie = report.trie();
+    assert_eq!(trie.len(), 4);
+    let stats = trie.stats();
+    assert_eq!((0..3).map(|d| stats.ones(d)).collect::<Vec<_>>(), vec![3, 1, 3]);
+    assert_eq!((0..3).map(|d| stats.zeros(d)).collect::<Vec<_>>(), vec![1, 3, 1]);
+
+    // Duplicate lines stay counted at the leaf, so the walk still ends there.
+    let most = trie.filter_by(Criterion::MOST_COMMON);
+    assert_eq!(format!("{:b}", most), "101");
+
+    // An empty branch is never taken, even when the policy asks for it.
+    let least = trie.filter_by(Criterion { keep: Keep::Minority, tie: false });
+    assert_eq!(format!("{:b}", least), "011");
+
+    // Any (set, unset) closure can drive the walk directly.
+    let zeros = trie.filter(|_, _| false);
+    assert_eq!(format!("{:b}", zeros), "011");
+  }
+
+  #[test]
+  fn ties() {
+    use crate::trie::{Criterion, Keep};
+
+    // Every position is a tie, which `ones > len / 2` used to resolve to 0
+    // for gamma without saying so.
+    let report: crate::Report = "10\n01\n".parse().unwrap();
+    let stats = report.trie().stats();
+    assert_eq!(format!("{:b}", stats.gamma()), "11");
+    assert_eq!(format!("{:b}", stats.epsilon()), "00");
+
+    let zeros_win = Criterion { keep: Keep::Majority, tie: false };
+    assert_eq!(format!("{:b}", stats.select(zeros_win)), "00");
+
+    // Odd line counts can't tie.
+    let report: crate::Report = "10\n01\n11\n".parse().unwrap();
+    let stats = report.trie().stats();
+    assert_eq!(format!("{:b}", stats.gamma()), "11");
+    assert_eq!(format!("{:b}", stats.epsilon()), "00");
+  }
+
+  #[test]
+  fn table() {
+    let report: crate::Report = include_str!("../sample.txt").parse().unwrap();
+    assert_eq!(
+      report.trie().stats().to_string(),
+      "  bit    ones   zeros gamma epsilon\n".to_owned()
+        + "    4       7       5     1       0\n"
+        + "    3       5       7     0       1\n"
+        + "    2       8       4     1       0\n"
+        + "    1       7       5     1       0\n"
+        + "    0       5       7     0       1\n"
+    );
+
+    let report: crate::Report = "10\n01\n".parse().unwrap();
+    assert_eq!(
+      report.trie().stats().to_string(),
+      "  bit    ones   zeros gamma epsilon\n".to_owned()
+        + "    1       1       1     1       0  (tie)\n"
+        + "    0       1       1     1       0  (tie)\n"
+    );
+  }
+
+  #[test]
+  fn inconsistent_width() {
+    let err = "0101\n011\n".parse::<crate::Report>().err().unwrap();
+    assert_eq!(err.to_string(), "line 2, column 4: expected 4 bits, found 3");
+
+    let err = "0101\n0121\n".parse::<crate::Report>().err().unwrap();
+    assert_eq!(err.to_string(), "line 2, column 3: expected a binary digit");
+  }
+
+  #[test]
+  fn wide() {
+    // 2^64 + 1 and its complement within 66 bits
+    let report: crate::Report = "010000000000000000000000000000000000000000000000000000000000000001\n"
+      .parse()
+      .unwrap();
+    assert_eq!(report.width, 66);
+
+    let stats = report.trie().stats();
+    let (gamma, epsilon) = (stats.gamma(), stats.epsilon());
+    assert_eq!(gamma.to_string(), "18446744073709551617");
+    assert_eq!(epsilon.to_string(), "55340232221128654846");
+    assert_eq!(format!("{:b}", epsilon), format!("10{}0", "1".repeat(63)));
+    assert_eq!(
+      (&gamma * &epsilon).to_string(),
+      "1020847100762815390408570566369014185982"
+    );
+  }
+
+  /// The ratings the way the puzzle describes them: filter the list one bit
+  /// at a time, keeping everything if a bit would filter out every value.
+  fn naive_rating(values: &[u64], width: usize, most_common: bool) -> u64 {
+    let mut values = values.to_vec();
+    for bit in (0..width).rev() {
+      if values.len() == 1 {
+        break;
+      }
+      let ones = values.iter().filter(|v| *v >> bit & 1 == 1).count();
+      let keep_ones = (ones * 2 >= values.len()) == most_common;
+      let kept = values
+        .iter()
+        .copied()
+        .filter(|v| (*v >> bit & 1 == 1) == keep_ones)
+        .collect::<Vec<_>>();
+      if !kept.is_empty() {
+        values = kept;
+      }
+    }
+    values[0]
+  }
+
+  fn report() -> impl Strategy<Value = (usize, Vec<u64>)> {
+    (1..=16usize).prop_flat_map(|width| (Just(width), prop::collection::vec(0..1u64 << width, 1..60)))
+  }
+
+  proptest! {
+    #[test]
+    fn matches_naive((width, values) in report()) {
+      let input = values.iter().map(|v| format!("{:0w$b}\n", v, w = width)).collect::<String>();
+      let report: crate::Report = input.parse().unwrap();
+      let trie = report.trie();
+
+      let ones = |bit: usize| values.iter().filter(|v| *v >> bit & 1 == 1).count();
+      let gamma = (0..width).rev().fold(0, |gamma, bit| gamma << 1 | (ones(bit) * 2 >= values.len()) as u64);
+      let epsilon = !gamma & ((1 << width) - 1);
+      let stats = trie.stats();
+      prop_assert_eq!(stats.gamma().to_string(), gamma.to_string());
+      prop_assert_eq!(stats.epsilon().to_string(), epsilon.to_string());
+
+      prop_assert_eq!(crate::oxygen(&trie).to_string(), naive_rating(&values, width, true).to_string());
+      prop_assert_eq!(crate::co2(&trie).to_string(), naive_rating(&values, width, false).to_string());
+    }
+  }
+}
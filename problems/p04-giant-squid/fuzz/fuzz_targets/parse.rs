@@ -0,0 +1,22 @@
+//! Any input either fails to parse or parses to draws and boards that print
+//! back to something that parses to the same draws and boards.
+
+#![no_main]
+
+use {
+  libfuzzer_sys::fuzz_target,
+  p04_giant_squid::parse,
+};
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(s) = std::str::from_utf8(data) else {
+    return;
+  };
+  let Ok(input) = parse::input(s) else {
+    return;
+  };
+
+  let printed = input.to_string();
+  let reparsed = parse::input(&printed).unwrap_or_else(|e| panic!("{:?} printed as {:?}: {}", input, printed, e));
+  assert_eq!(reparsed, input);
+});
@@ -0,0 +1,235 @@
+//! What-if analysis of a bingo game: when each board would win, which boards
+//! are the most dangerous, and how that changes if the draw is different.
+
+use {
+  super::{BingoBoard, Game, Win, WinRules},
+  anyhow::{anyhow, Error},
+  std::fmt,
+  util::generators::Rng,
+};
+
+/// A change to the draw order.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum Scenario {
+  /// Keep only the first `n` draws.
+  Truncate(usize),
+  /// Shuffle the draws, deterministically for a given seed.
+  Shuffle(u64),
+  /// Never draw these numbers.
+  Remove(Vec<u32>),
+}
+
+impl Scenario {
+  pub fn apply(&self, draws: &[u32]) -> Vec<u32> {
+    match self {
+      Scenario::Truncate(n) => draws.iter().take(*n).copied().collect(),
+      Scenario::Shuffle(seed) => {
+        let mut draws = draws.to_vec();
+        Rng::new(*seed).shuffle(&mut draws);
+        draws
+      }
+      Scenario::Remove(nums) => draws.iter().filter(|n| !nums.contains(n)).copied().collect(),
+    }
+  }
+
+  /// Parses a command line flag and its value, e.g. `--shuffle 42`.
+  pub fn from_flag(flag: &str, value: &str) -> Result<Scenario, Error> {
+    match flag {
+      "--truncate" => Ok(Scenario::Truncate(value.parse()?)),
+      "--shuffle" => Ok(Scenario::Shuffle(value.parse()?)),
+      "--remove" => Ok(Scenario::Remove(
+        value
+          .split(',')
+          .map(|n| n.trim().parse().map_err(Error::from))
+          .collect::<Result<_, Error>>()?,
+      )),
+      other => Err(anyhow!("Unknown scenario: {}", other)),
+    }
+  }
+}
+
+/// How a single board fares against the draw.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) struct BoardReport {
+  pub board: usize,
+  pub win: Option<Win>,
+  /// How many more cells it would need to win once the draw runs out. Zero for
+  /// boards that won.
+  pub cells_to_win: usize,
+  /// One for the board that wins first. Boards that never win are ranked
+  /// after all those that do, closest to winning first.
+  pub danger: usize,
+}
+
+pub(crate) struct Analysis {
+  pub draws: Vec<u32>,
+  /// In board order.
+  pub boards: Vec<BoardReport>,
+}
+
+impl Analysis {
+  /// The best board to play, for part 1.
+  pub fn first_winner(&self) -> Option<&BoardReport> {
+    self.winners_after(self.draws.len()).first().copied()
+  }
+
+  /// The worst board to play, for letting the squid win in part 2. Only known
+  /// if every board wins.
+  pub fn last_winner(&self) -> Option<&BoardReport> {
+    let winners = self.winners_after(self.draws.len());
+    winners.last().copied().filter(|_| winners.len() == self.boards.len())
+  }
+
+  /// The boards that have won within the first `draws` draws, in the order
+  /// they won. The first and last of these are the best and worst boards had
+  /// the game stopped there.
+  pub fn winners_after(&self, draws: usize) -> Vec<&BoardReport> {
+    let mut winners = self
+      .boards
+      .iter()
+      .filter(|b| b.win.as_ref().is_some_and(|w| w.draw < draws))
+      .collect::<Vec<_>>();
+    winners.sort_by_key(|b| b.danger);
+    winners
+  }
+}
+
+/// Boards don't affect each other, so this is just the game played out to the
+/// end, with the boards ranked by when (or whether) they win.
+pub(crate) fn analyse(boards: &[BingoBoard], draws: &[u32], rules: WinRules) -> Analysis {
+  let mut game = Game::new(boards.to_vec(), rules);
+  let mut wins = game.play(draws).into_iter().map(Some).collect::<Vec<_>>();
+
+  let mut reports = game
+    .boards
+    .iter()
+    .enumerate()
+    .map(|(i, board)| BoardReport {
+      board: i,
+      win: wins.iter_mut().find(|w| matches!(w, Some(w) if w.board == i)).and_then(Option::take),
+      cells_to_win: board.cells_to_win(rules),
+      danger: 0,
+    })
+    .collect::<Vec<_>>();
+
+  let mut ranking = (0..reports.len()).collect::<Vec<_>>();
+  ranking.sort_by_key(|i| {
+    let report = &reports[*i];
+    let draw = report.win.as_ref().map_or(usize::MAX, |w| w.draw);
+    (draw, report.cells_to_win, report.board)
+  });
+  for (rank, i) in ranking.into_iter().enumerate() {
+    reports[i].danger = rank + 1;
+  }
+
+  Analysis {
+    draws: draws.to_vec(),
+    boards: reports,
+  }
+}
+
+/// Re-runs the analysis with each scenario applied to the draw in turn.
+pub(crate) fn what_if(boards: &[BingoBoard], draws: &[u32], rules: WinRules, scenarios: &[Scenario]) -> Analysis {
+  let draws = scenarios.iter().fold(draws.to_vec(), |draws, s| s.apply(&draws));
+  analyse(boards, &draws, rules)
+}
+
+impl fmt::Display for Analysis {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    writeln!(f, "{} draws", self.draws.len())?;
+    writeln!(
+      f,
+      "{:>6} {:>7} {:>7} {:>8} {:>7} {:>7}",
+      "board", "draw", "number", "score", "to win", "danger"
+    )?;
+    for report in &self.boards {
+      let (draw, number, score) = match &report.win {
+        Some(win) => (win.draw.to_string(), win.number.to_string(), win.score.to_string()),
+        None => ("-".to_owned(), "-".to_owned(), "-".to_owned()),
+      };
+      writeln!(
+        f,
+        "{:>6} {:>7} {:>7} {:>8} {:>7} {:>7}",
+        report.board, draw, number, score, report.cells_to_win, report.danger
+      )?;
+    }
+    let board = |report: Option<&BoardReport>| report.map_or_else(|| "-".to_owned(), |r| r.board.to_string());
+    writeln!(f)?;
+    writeln!(f, "{:<10} {}", "wins first", board(self.first_winner()))?;
+    writeln!(f, "{:<10} {}", "wins last", board(self.last_winner()))?;
+    Ok(())
+  }
+}
+
+mod tests {
+  #[test]
+  fn sample() {
+    use {
+      super::analyse,
+      crate::{parse, WinRules},
+    };
+
+    let parse::Input { draws, boards } = parse::input(include_str!("../sample.txt")).unwrap();
+    let analysis = analyse(&boards, &draws, WinRules::default());
+
+    let summary = analysis
+      .boards
+      .iter()
+      .map(|b| (b.win.as_ref().map(|w| w.draw), b.danger))
+      .collect::<Vec<_>>();
+    assert_eq!(summary, vec![(Some(13), 2), (Some(14), 3), (Some(11), 1)]);
+    assert_eq!(analysis.first_winner().unwrap().board, 2);
+    assert_eq!(analysis.last_winner().unwrap().win.as_ref().unwrap().score, 1924);
+
+    let after = |draws| analysis.winners_after(draws).iter().map(|b| b.board).collect::<Vec<_>>();
+    assert_eq!(after(11), Vec::<usize>::new());
+    assert_eq!(after(12), vec![2]);
+    assert_eq!(after(14), vec![2, 0]);
+    assert_eq!(after(draws.len()), vec![2, 0, 1]);
+  }
+
+  #[test]
+  fn scenarios() {
+    use {
+      super::{what_if, Scenario},
+      crate::{parse, WinRules},
+    };
+
+    let parse::Input { draws, boards } = parse::input(include_str!("../sample.txt")).unwrap();
+    let rules = WinRules::default();
+
+    // Cut off just before board 0 wins: only board 2 has won, and board 0 is
+    // one cell closer than board 1.
+    let analysis = what_if(&boards, &draws, rules, &[Scenario::Truncate(13)]);
+    let summary = analysis
+      .boards
+      .iter()
+      .map(|b| (b.win.is_some(), b.cells_to_win, b.danger))
+      .collect::<Vec<_>>();
+    assert_eq!(summary, vec![(false, 1, 2), (false, 1, 3), (true, 0, 1)]);
+    assert!(analysis.last_winner().is_none());
+
+    // Without 24, board 2's winning row never completes on draw 11.
+    let analysis = what_if(&boards, &draws, rules, &[Scenario::Remove(vec![24])]);
+    assert_eq!(analysis.draws.len(), draws.len() - 1);
+    assert_ne!(analysis.first_winner().unwrap().win.as_ref().unwrap().draw, 11);
+
+    let shuffled = Scenario::Shuffle(7).apply(&draws);
+    assert_eq!(shuffled, Scenario::Shuffle(7).apply(&draws));
+    assert_ne!(shuffled, draws);
+    let mut sorted = shuffled.clone();
+    sorted.sort_unstable();
+    let mut expected = draws.clone();
+    expected.sort_unstable();
+    assert_eq!(sorted, expected);
+  }
+
+  #[test]
+  fn flags() {
+    use super::Scenario;
+
+    assert_eq!(Scenario::from_flag("--truncate", "5").unwrap(), Scenario::Truncate(5));
+    assert_eq!(Scenario::from_flag("--remove", "1, 2").unwrap(), Scenario::Remove(vec![1, 2]));
+    assert!(Scenario::from_flag("--reverse", "1").is_err());
+  }
+}
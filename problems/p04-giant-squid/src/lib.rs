@@ -0,0 +1,405 @@
+//! Bingo boards, which track their marked cells as numbers are drawn, the
+//! rules for which lines win one, and the parser for the draws and boards.
+
+use {
+  anyhow::{anyhow, Error},
+  bitflags::bitflags,
+  std::{
+    fmt,
+    ops::{Index, IndexMut},
+    str::FromStr,
+  },
+};
+
+bitflags! {
+  /// Which completed lines win a board. Diagonals only count on square boards.
+  pub struct WinRules: u8 {
+    const ROWS = 1 << 0;
+    const COLUMNS = 1 << 1;
+    const DIAGONALS = 1 << 2;
+    const FULL_CARD = 1 << 3;
+  }
+}
+
+impl Default for WinRules {
+  fn default() -> WinRules {
+    WinRules::ROWS | WinRules::COLUMNS
+  }
+}
+
+impl FromStr for WinRules {
+  type Err = Error;
+
+  /// Parses a comma-separated list like `rows,columns,diagonals`.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    s.split(',')
+      .map(|rule| match rule.trim() {
+        "rows" => Ok(WinRules::ROWS),
+        "columns" => Ok(WinRules::COLUMNS),
+        "diagonals" => Ok(WinRules::DIAGONALS),
+        "full" => Ok(WinRules::FULL_CARD),
+        other => Err(anyhow!("Unknown win rule: {}", other)),
+      })
+      .collect()
+  }
+}
+
+/// A `height` by `width` bingo board.
+///
+/// Rather than a bitmask per row and column, which would limit the board
+/// size, each row, column and diagonal keeps a count of its marked cells.
+#[derive(Default, Clone, PartialEq, Eq)]
+pub struct BingoBoard {
+  pub width: usize,
+  pub height: usize,
+  nums: Vec<u32>,
+  pub marked: Vec<bool>,
+  rows: Vec<usize>,
+  cols: Vec<usize>,
+  /// Leading and trailing diagonals, only updated on square boards.
+  diags: [usize; 2],
+  total: usize,
+}
+
+impl fmt::Debug for BingoBoard {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for x in 0..self.height {
+      for y in 0..self.width {
+        let mark = if self.marked[x * self.width + y] { '*' } else { ' ' };
+        write!(f, "{:3}{} ", self[(x, y)], mark)?;
+      }
+      writeln!(f)?;
+    }
+    writeln!(f, "{:?}", self.rows)?;
+    writeln!(f, "{:?}", self.cols)?;
+    Ok(())
+  }
+}
+
+impl BingoBoard {
+  fn new(height: usize, width: usize, nums: Vec<u32>) -> BingoBoard {
+    assert_eq!(nums.len(), width * height);
+    BingoBoard {
+      width,
+      height,
+      marked: vec![false; nums.len()],
+      nums,
+      rows: vec![0; height],
+      cols: vec![0; width],
+      diags: [0, 0],
+      total: 0,
+    }
+  }
+
+  /// Marks the cell at row `x`, column `y`, returning whether it wasn't
+  /// already marked.
+  pub fn mark_cell(&mut self, x: usize, y: usize) -> bool {
+    let cell = &mut self.marked[x * self.width + y];
+    if *cell {
+      return false;
+    }
+    *cell = true;
+    self.rows[x] += 1;
+    self.cols[y] += 1;
+    if self.width == self.height {
+      if x == y {
+        self.diags[0] += 1;
+      }
+      if x + y == self.width - 1 {
+        self.diags[1] += 1;
+      }
+    }
+    self.total += 1;
+    true
+  }
+
+  pub fn score(&self) -> u64 {
+    self
+      .nums
+      .iter()
+      .zip(self.marked.iter())
+      .filter(|(_, marked)| !**marked)
+      .map(|(n, _)| *n as u64)
+      .sum()
+  }
+
+  pub fn finished(&self, rules: WinRules) -> bool {
+    (rules.contains(WinRules::ROWS) && self.rows.contains(&self.width))
+      || (rules.contains(WinRules::COLUMNS) && self.cols.contains(&self.height))
+      || (rules.contains(WinRules::DIAGONALS)
+        && self.width == self.height
+        && self.diags.contains(&self.width))
+      || (rules.contains(WinRules::FULL_CARD) && self.total == self.nums.len())
+  }
+
+  /// The fewest cells that still need marking for the board to win, which is
+  /// zero once it has.
+  pub fn cells_to_win(&self, rules: WinRules) -> usize {
+    let mut best = usize::MAX;
+    if rules.contains(WinRules::ROWS) {
+      best = best.min(self.width - self.rows.iter().max().unwrap_or(&0));
+    }
+    if rules.contains(WinRules::COLUMNS) {
+      best = best.min(self.height - self.cols.iter().max().unwrap_or(&0));
+    }
+    if rules.contains(WinRules::DIAGONALS) && self.width == self.height {
+      best = best.min(self.width - self.diags.iter().max().unwrap_or(&0));
+    }
+    if rules.contains(WinRules::FULL_CARD) {
+      best = best.min(self.nums.len() - self.total);
+    }
+    best
+  }
+
+  /// The (row, column) of every cell in a completed line that wins under
+  /// `rules`, in reading order.
+  pub fn winning_cells(&self, rules: WinRules) -> Vec<(usize, usize)> {
+    let mut cells = Vec::new();
+    if rules.contains(WinRules::ROWS) {
+      for x in (0..self.height).filter(|x| self.rows[*x] == self.width) {
+        cells.extend((0..self.width).map(|y| (x, y)));
+      }
+    }
+    if rules.contains(WinRules::COLUMNS) {
+      for y in (0..self.width).filter(|y| self.cols[*y] == self.height) {
+        cells.extend((0..self.height).map(|x| (x, y)));
+      }
+    }
+    if rules.contains(WinRules::DIAGONALS) && self.width == self.height {
+      if self.diags[0] == self.width {
+        cells.extend((0..self.width).map(|i| (i, i)));
+      }
+      if self.diags[1] == self.width {
+        cells.extend((0..self.width).map(|i| (i, self.width - 1 - i)));
+      }
+    }
+    if rules.contains(WinRules::FULL_CARD) && self.total == self.nums.len() {
+      cells.extend((0..self.height).flat_map(|x| (0..self.width).map(move |y| (x, y))));
+    }
+    cells.sort_unstable();
+    cells.dedup();
+    cells
+  }
+}
+
+/// Prints the board the way the input writes it, a row per line.
+impl fmt::Display for BingoBoard {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (x, row) in self.nums.chunks(self.width.max(1)).enumerate() {
+      if x > 0 {
+        writeln!(f)?;
+      }
+      let row = row.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+      write!(f, "{}", row.join(" "))?;
+    }
+    Ok(())
+  }
+}
+
+impl Index<(usize, usize)> for BingoBoard {
+  type Output = u32;
+
+  fn index(&self, index: (usize, usize)) -> &Self::Output {
+    let (x, y) = index;
+    &self.nums[x * self.width + y]
+  }
+}
+
+impl IndexMut<(usize, usize)> for BingoBoard {
+  fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
+    let (x, y) = index;
+    &mut self.nums[x * self.width + y]
+  }
+}
+
+impl FromStr for BingoBoard {
+  type Err = Error;
+
+  /// Accepts any rectangular board, one row per line.
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    parse::board(s)
+  }
+}
+
+pub mod parse {
+  use {
+    super::BingoBoard,
+    anyhow::Error,
+    nom::{
+      branch::alt,
+      character::complete::{line_ending, space0, space1},
+      combinator::{consumed, eof, value},
+      multi::{many1, separated_list1},
+      sequence::{delimited, pair},
+      IResult,
+    },
+    std::{collections::BTreeMap, fmt, io::BufRead},
+    util::{
+      error::Diagnostic,
+      parse::{blocks, comma_separated, error_at, nom_error, position, unsigned},
+    },
+  };
+
+  #[derive(Debug, PartialEq, Eq)]
+  pub struct Input {
+    pub draws: Vec<u32>,
+    pub boards: Vec<BingoBoard>,
+  }
+
+  /// Prints the draws and boards the way the input writes them.
+  impl fmt::Display for Input {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      let draws = self.draws.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+      write!(f, "{}", draws.join(","))?;
+      for board in &self.boards {
+        write!(f, "\n\n{}", board)?;
+      }
+      Ok(())
+    }
+  }
+
+  /// The end of a line, ignoring trailing whitespace, or of the input.
+  fn end_of_line(i: &str) -> IResult<&str, ()> {
+    value((), pair(space0, alt((line_ending, eof))))(i)
+  }
+
+  fn draws(i: &str) -> IResult<&str, Vec<u32>> {
+    delimited(space0, comma_separated, end_of_line)(i)
+  }
+
+  /// A row of a board, along with its text so errors can point at it.
+  fn row(i: &str) -> IResult<&str, (&str, Vec<u32>)> {
+    consumed(delimited(space0, separated_list1(space1, unsigned), end_of_line))(i)
+  }
+
+  /// Checks the rows all have the same length and hold distinct numbers.
+  fn board_from_rows(full: &str, rows: &[(&str, Vec<u32>)]) -> Result<BingoBoard, Error> {
+    let width = rows[0].1.len();
+    let mut seen = BTreeMap::<u32, usize>::new();
+    for (text, nums) in rows {
+      // The row without its line ending or surrounding space.
+      let text = text.trim();
+      if nums.len() != width {
+        return Err(Diagnostic::spanning(full, text, format_args!("board row has {} numbers, expected {}", nums.len(), width)).into());
+      }
+      let line = position(full, text).0;
+      for n in nums {
+        if let Some(prev) = seen.insert(*n, line) {
+          return Err(
+            Diagnostic::spanning(full, text, format_args!("{} is already on this board, on line {}", n, prev)).into(),
+          );
+        }
+      }
+    }
+    Ok(BingoBoard::new(
+      rows.len(),
+      width,
+      rows.iter().flat_map(|(_, nums)| nums.iter().copied()).collect(),
+    ))
+  }
+
+  /// Parses `block`, a slice of `full` with no blank lines in it, as a board.
+  fn board_in(full: &str, block: &str) -> Result<BingoBoard, Error> {
+    let (rest, rows) = many1(row)(block).map_err(|e| nom_error(full, e, "a row of numbers"))?;
+    if !rest.is_empty() {
+      // many1 stops at the first row that doesn't parse, so parse it again to
+      // find out where it went wrong.
+      return Err(match row(rest) {
+        Err(e) => nom_error(full, e, "a row of numbers"),
+        Ok(_) => error_at(full, rest, "expected a row of numbers"),
+      });
+    }
+    board_from_rows(full, &rows)
+  }
+
+  /// Parses a single board, which may be surrounded by blank lines.
+  pub fn board(s: &str) -> Result<BingoBoard, Error> {
+    let mut blocks = blocks(s);
+    let board = board_in(s, blocks.next().unwrap_or(s))?;
+    match blocks.next() {
+      Some(extra) => Err(error_at(s, extra, "expected end of board")),
+      None => Ok(board),
+    }
+  }
+
+  /// Parses the draws on the first line followed by boards, which must all be
+  /// the same size. Any number of blank lines can separate the boards.
+  pub fn input(s: &str) -> Result<Input, Error> {
+    let mut blocks = blocks(s);
+    let (rest, draws) = draws(blocks.next().unwrap_or(s)).map_err(|e| nom_error(s, e, "comma-separated draws"))?;
+
+    // The first board might follow the draws without a blank line between.
+    let mut boards: Vec<BingoBoard> = Vec::new();
+    for block in Some(rest).filter(|rest| !rest.is_empty()).into_iter().chain(blocks) {
+      let board = board_in(s, block)?;
+      if let Some(first) = boards.first() {
+        if (first.height, first.width) != (board.height, board.width) {
+          let message = format!(
+            "board is {}x{}, expected {}x{}",
+            board.height, board.width, first.height, first.width
+          );
+          return Err(Diagnostic::spanning(s, block, message).into());
+        }
+      }
+      boards.push(board);
+    }
+
+    Ok(Input { draws, boards })
+  }
+
+  pub fn read(mut reader: impl BufRead) -> Result<Input, Error> {
+    let mut s = String::new();
+    reader.read_to_string(&mut s)?;
+    input(&s)
+  }
+} // mod parse
+
+mod tests {
+  #[test]
+  fn messy_input() {
+    use crate::parse;
+
+    let input = parse::input(
+      "\n 7, 4,9 \n\n\n 1  2\t\n3 4 \n  \n\n\n5 6\n7 8\n\n",
+    )
+    .unwrap();
+    assert_eq!(input.draws, vec![7, 4, 9]);
+    assert_eq!(input.boards.len(), 2);
+    assert_eq!(input.boards[1][(1, 0)], 7);
+
+    // No trailing newline, and no boards at all.
+    assert_eq!(parse::input("1,2").unwrap().boards.len(), 0);
+  }
+
+  #[test]
+  fn parse_errors() {
+    use crate::{parse, BingoBoard};
+
+    let err = |s: &str| parse::input(s).err().unwrap().to_string();
+
+    assert_eq!(err("1,2,x\n\n1 2\n3 4\n"), "line 1, column 4: expected comma-separated draws");
+    assert_eq!(err("1,2\n\n1 2\n3 x\n"), "line 4, column 3: expected a row of numbers");
+    assert_eq!(err("1,2\n\n1 2\n3 4 5\n"), "line 4, column 1: board row has 3 numbers, expected 2");
+    assert_eq!(err("1,2\n\n1 2\n 3 1\n"), "line 4, column 2: 1 is already on this board, on line 3");
+    assert_eq!(
+      err("1,2\n\n1 2\n3 4\n\n\n1 2 3\n4 5 6\n"),
+      "line 7, column 1: board is 2x3, expected 2x2"
+    );
+
+    assert_eq!(
+      "1 2\n3\n".parse::<BingoBoard>().err().unwrap().to_string(),
+      "line 2, column 1: board row has 1 numbers, expected 2"
+    );
+  }
+
+  #[test]
+  fn round_trip() {
+    use crate::parse;
+
+    let input = include_str!("../sample.txt");
+    let parsed = parse::input(input).unwrap();
+    assert_eq!(parse::input(&parsed.to_string()).unwrap(), parsed);
+    assert_eq!(parsed.boards[0].to_string().lines().next(), Some("22 13 17 11 0"));
+    assert_eq!(parse::input("1,2").unwrap().to_string(), "1,2");
+  }
+}
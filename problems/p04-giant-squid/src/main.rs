@@ -1,213 +1,284 @@
+mod analysis;
+mod replay;
+
 use {
+  analysis::Scenario,
   anyhow::{anyhow, Error},
+  p04_giant_squid::{parse, BingoBoard, WinRules},
   std::{
-    fmt, io,
-    ops::{Index, IndexMut},
-    str::FromStr,
+    collections::BTreeMap,
+    env, fs,
+    io::{self, IsTerminal},
+    time::Duration,
+  },
+  tracing::{debug, info, info_span},
+  util::{
+    answers::{Answers, Options},
+    error::{self, Report},
+    trace,
   },
 };
 
-#[derive(Default)]
-struct BingoBoard {
-  nums: [u8; 25],
-  rows: [u8; 5],
-  cols: [u8; 5],
+/// A board winning: which board, at which position in the draw order, and
+/// with what score.
+#[derive(Debug, PartialEq, Eq)]
+struct Win {
+  board: usize,
+  draw: usize,
+  number: u32,
+  score: u64,
 }
 
-impl fmt::Debug for BingoBoard {
-  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-    for x in 0..5 {
-      for y in 0..5 {
-        write!(f, "{:3} ", self[(x, y)])?;
-      }
-      writeln!(f)?;
-    }
-    writeln!(f, "{:?}", self.rows)?;
-    writeln!(f, "{:?}", self.cols)?;
-    Ok(())
-  }
+/// Plays a set of boards against a sequence of draws.
+///
+/// Every number's positions are looked up once up front, so each draw only
+/// touches the cells it actually hits rather than scanning every board.
+struct Game {
+  boards: Vec<BingoBoard>,
+  rules: WinRules,
+  /// Number -> (board, row, column) of every cell holding it.
+  index: BTreeMap<u32, Vec<(usize, usize, usize)>>,
+  won: Vec<bool>,
+  draws: usize,
 }
 
-impl BingoBoard {
-  fn mark(&mut self, val: u8) {
-    for x in 0..5 {
-      for y in 0..5 {
-        if self[(x, y)] == val {
-          self.rows[x] |= 1 << y;
-          self.cols[y] |= 1 << x;
+impl Game {
+  fn new(boards: Vec<BingoBoard>, rules: WinRules) -> Game {
+    let mut index: BTreeMap<u32, Vec<(usize, usize, usize)>> = BTreeMap::new();
+    for (i, board) in boards.iter().enumerate() {
+      for x in 0..board.height {
+        for y in 0..board.width {
+          index.entry(board[(x, y)]).or_default().push((i, x, y));
         }
       }
     }
+    Game {
+      won: vec![false; boards.len()],
+      boards,
+      rules,
+      index,
+      draws: 0,
+    }
   }
 
-  fn reset(&mut self) {
-    for x in 0..5 { self.rows[x] = 0; self.cols[x] = 0; }
-  }
+  /// Draws `n`, returning any boards that won because of it, in board order.
+  fn draw(&mut self, n: u32) -> Vec<Win> {
+    let draw = self.draws;
+    self.draws += 1;
 
-  fn score(&self) -> u32 {
-    let mut sum = 0u32;
-    for x in 0..5 {
-      for y in 0..5 {
-        if !self.rows[x] & (1 << y) > 0 {
-          sum += self[(x, y)] as u32;
-        }
+    let mut wins = Vec::new();
+    for (i, x, y) in self.index.get(&n).into_iter().flatten() {
+      let board = &mut self.boards[*i];
+      if board.mark_cell(*x, *y) && !self.won[*i] && board.finished(self.rules) {
+        self.won[*i] = true;
+        wins.push(Win {
+          board: *i,
+          draw,
+          number: n,
+          score: board.score() * n as u64,
+        });
       }
     }
-
-    sum
+    wins.sort_by_key(|win| win.board);
+    wins
   }
 
-  fn finished(&self) -> bool {
-    for r in self.rows.iter().chain(self.cols.iter()) {
-      if *r == (1 << 5) - 1 {
-        return true;
+  /// Plays every draw, returning the boards in the order they won. Boards that
+  /// never win are left out.
+  fn play(&mut self, draws: &[u32]) -> Vec<Win> {
+    let mut wins = Vec::new();
+    for n in draws {
+      wins.extend(self.draw(*n));
+      if self.all_won() {
+        break;
       }
     }
-    false
-  }
-}
-
-impl Index<(usize, usize)> for BingoBoard {
-  type Output = u8;
-
-  fn index(&self, index: (usize, usize)) -> &Self::Output {
-    let (x, y) = index;
-    &self.nums[x * 5 + y]
+    wins
   }
-}
 
-impl IndexMut<(usize, usize)> for BingoBoard {
-  fn index_mut(&mut self, index: (usize, usize)) -> &mut Self::Output {
-    let (x, y) = index;
-    &mut self.nums[x * 5 + y]
+  fn all_won(&self) -> bool {
+    self.won.iter().all(|won| *won)
   }
 }
 
-impl FromStr for BingoBoard {
-  type Err = Error;
+/// Plays the game, scoring the first and last boards to win.
+fn solve(boards: Vec<BingoBoard>, draws: &[u32], rules: WinRules) -> Result<Answers, Error> {
+  let mut game = Game::new(boards, rules);
+  let mut draws = draws.iter();
 
-  fn from_str(s: &str) -> Result<Self, Self::Err> {
-    let mut out = BingoBoard::default();
+  // Part 1 plays until a board wins, and part 2 carries on from there.
+  let mut wins = info_span!("part1").in_scope(|| {
+    draws
+      .by_ref()
+      .map(|n| game.draw(*n))
+      .find(|wins| !wins.is_empty())
+      .ok_or_else(|| anyhow!("No board won"))
+  })?;
+  let first = wins[0].score;
 
-    for (i, line) in s.lines().enumerate() {
-      for (j, c) in line.split_whitespace().enumerate() {
-        out[(i, j)] = c.parse()?;
+  let last = info_span!("part2").in_scope(|| {
+    for n in draws {
+      if game.all_won() {
+        break;
       }
+      wins.extend(game.draw(*n));
     }
-
-    Ok(out)
-  }
-}
-
-pub trait DiscardInput {
-  fn discard_line(&mut self) -> Result<bool, io::Error>;
+    // The whole finishing order, which `-v` shows.
+    for win in &wins {
+      info!("Board {} finished on draw {} ({}) with score {}", win.board, win.draw, win.number, win.score);
+    }
+    if !game.all_won() {
+      return Err(anyhow!("Not every board won"));
+    }
+    let last = wins.last().unwrap();
+    debug!("Last board to win:\n{:?}", game.boards[last.board]);
+    Ok(last.score)
+  })?;
+  Ok(Answers::new(first, last))
 }
 
-impl DiscardInput for io::Stdin {
-  fn discard_line(&mut self) -> Result<bool, io::Error> {
-    use std::io::BufRead;
-    let mut buf = self.lock();
-    loop {
-      let (consume, done) = {
-        let data = buf.fill_buf()?;
-        if data.is_empty() {
-          return Ok(false);
-        } else if let Some(nl) = data.iter().position(|b| *b == b'\n') {
-          (nl + 1, true)
-        } else {
-          (data.len(), false)
-        }
-      };
-      buf.consume(consume);
-      if done {
-        break;
+fn main() -> Result<(), Report> {
+  // `p04-giant-squid rows,columns,diagonals,full` picks which lines win a
+  // board; the default is rows and columns. `-v` lists every board in the
+  // order it finished.
+  //
+  // `--truncate N`, `--shuffle SEED` and `--remove a,b,c` change the draw
+  // order, in the order given, and `analyse` prints when every board would
+  // win instead of playing the game.
+  //
+  // `replay FILE` steps through the game in FILE interactively, taking
+  // commands from stdin.
+  let (options, args) = Options::from_args(env::args().skip(1))?;
+  trace::init(options.verbosity)?;
+  let mut rules = WinRules::default();
+  let mut scenarios = Vec::new();
+  let mut analyse = false;
+  let mut replay = None;
+  let mut args = args.into_iter();
+  while let Some(arg) = args.next() {
+    match arg.as_str() {
+      "analyse" => analyse = true,
+      "replay" => replay = Some(args.next().ok_or_else(|| anyhow!("Missing file to replay"))?),
+      flag if flag.starts_with("--") => {
+        let value = args
+          .next()
+          .ok_or_else(|| anyhow!("Missing value for {}", flag))?;
+        scenarios.push(Scenario::from_flag(flag, &value)?);
       }
+      _ => rules = arg.parse()?,
     }
-    Ok(true)
   }
+
+  if let Some(path) = replay {
+    let parse::Input { draws, boards } =
+      parse::input(&fs::read_to_string(&path)?).map_err(|e| error::in_file(e, &path))?;
+    let draws = scenarios.iter().fold(draws, |draws, s| s.apply(&draws));
+    let mut replay = replay::Replay::new(boards, draws, rules);
+    let colour = io::stdout().is_terminal();
+    return Ok(replay::run(
+      &mut replay,
+      io::stdin().lock(),
+      io::stdout().lock(),
+      colour,
+      Duration::from_millis(150),
+    )?);
+  }
+
+  if analyse {
+    let parse::Input { draws, boards } = parse::read(io::stdin().lock())?;
+    print!("{}", analysis::what_if(&boards, &draws, rules, &scenarios));
+    return Ok(());
+  }
+
+  Ok(options.run(|input| {
+    let parse::Input { draws, boards } = info_span!("parse").in_scope(|| parse::input(input))?;
+    let draws = scenarios.iter().fold(draws, |draws, s| s.apply(&draws));
+    solve(boards, &draws, rules)
+  })?)
 }
 
-fn main() -> Result<(), Error> {
-  // use std::collections::BTreeMap;
+mod tests {
+  #[test]
+  fn sample() {
+    use crate::{parse, Game, Win, WinRules};
 
-  let input = io::stdin()
-    .lines()
-    .take(1)
-    .next()
-    .ok_or_else(|| anyhow!("Ran out of input"))??
-    .split(',')
-    .map(|s| s.parse().map_err(Error::from))
-    .collect::<Result<Vec<u8>, Error>>()?;
+    let parse::Input { draws, boards } = parse::input(include_str!("../sample.txt")).unwrap();
+    let mut game = Game::new(boards, WinRules::default());
+    let wins = game.play(&draws);
 
-  let mut boards: Vec<BingoBoard> = Vec::new();
+    assert_eq!(
+      wins,
+      vec![
+        Win { board: 2, draw: 11, number: 24, score: 4512 },
+        Win { board: 0, draw: 13, number: 16, score: 2192 },
+        Win { board: 1, draw: 14, number: 13, score: 1924 },
+      ]
+    );
 
-  loop {
-    if !io::stdin().discard_line()? {
-      break;
-    }
-    let mut board_in = String::new();
-    for _ in 0..5 {
-      io::stdin().read_line(&mut board_in)?;
-    }
-    let board: BingoBoard = board_in.parse()?;
-    boards.push(board);
+    let parse::Input { draws, boards } = parse::input(include_str!("../sample.txt")).unwrap();
+    assert_eq!(
+      crate::solve(boards, &draws, WinRules::default()).unwrap(),
+      util::answers::Answers::new(4512, 1924)
+    );
   }
 
-  // dbg!(&boards);
-
-  // let mut index: BTreeMap<u8, Vec<usize>> = BTreeMap::new();
-  // for (i, board) in boards.iter().enumerate() {
-  //   for n in board.nums {
-  //     index.entry(n).or_default().push(i)
-  //   }
-  // }
-
-  // Part 1
-  {
-    let mut winner = None;
-    let mut val = None;
-    'outer: for n in input.iter() {
-      for (i, board) in boards.iter_mut().enumerate() {
-        board.mark(*n);
-        if board.finished() {
-          println!("Board {} finished", i);
-          winner = Some(i);
-          val = Some(*n as u32);
-          break 'outer;
-        }
-      }
-    }
-    // dbg!(&boards);
+  #[test]
+  fn generated() {
+    use p04_giant_squid::{parse, WinRules};
+
+    util::generators::check(4, 100, |s| {
+      let parse::Input { draws, boards } = parse::input(s)?;
+      crate::solve(boards, &draws, WinRules::default())
+    });
+  }
 
-    let (Some(winner), Some(val)) = (winner, val) else {
-    return Err(anyhow!("??"));
-  };
+  #[test]
+  fn rectangular() {
+    use crate::{BingoBoard, Game, WinRules};
 
-    dbg!(boards[winner].score() * val);
+    let draws = [1000, 2, 3000, 4, 5, 6];
+    let boards = ["1000 2\n3000 4\n5 6", "7 8\n9 10\n11 12"]
+      .map(|b| b.parse::<BingoBoard>().unwrap());
+    assert_eq!((boards[0].height, boards[0].width), (3, 2));
+
+    // Column 0 of board 0 is complete once 5 is drawn, leaving only 6. Board 1
+    // never wins.
+    let wins = Game::new(boards.to_vec(), WinRules::COLUMNS).play(&draws);
+    assert_eq!(wins.len(), 1);
+    assert_eq!((wins[0].board, wins[0].draw, wins[0].score), (0, 4, 6 * 5));
   }
 
-  for board in boards.iter_mut() { board.reset(); }
+  #[test]
+  fn simultaneous() {
+    use crate::{BingoBoard, Game, WinRules};
 
-  // Part 2
-  {
-    let mut val = None;
-    for n in input {
-      dbg!(boards.len());
-      for board in boards.iter_mut() {
-        board.mark(n);
-      }
-      if boards.len() == 1 && boards[0].finished() {
-        val = Some(n as u32);
-        break;
-      }
-      boards.retain(|b| !b.finished());
-    }
-    let (winner, Some(val)) = (&boards[0], val) else {
-      return Err(anyhow!("??"));
-    };
-    dbg!(winner.score() * val);
-    dbg!(winner);
+    // Boards 0 and 2 both win on the second draw; they're reported in board
+    // order.
+    let boards = ["1 2\n3 4", "5 6\n7 8", "2 1\n9 10"].map(|b| b.parse::<BingoBoard>().unwrap());
+    let wins = Game::new(boards.to_vec(), WinRules::default()).play(&[1, 2, 9, 5, 7]);
+    let order = wins.iter().map(|w| (w.board, w.draw)).collect::<Vec<_>>();
+    assert_eq!(order, vec![(0, 1), (2, 1), (1, 4)]);
+    assert_eq!(wins[1].score, (9 + 10) * 2);
   }
 
-  Ok(())
+  #[test]
+  fn rules() {
+    use crate::{BingoBoard, Game, WinRules};
+
+    let draws = [1, 5, 9, 2, 3, 4, 6, 7, 8];
+    let board: BingoBoard = "1 2 3\n4 5 6\n7 8 9".parse().unwrap();
+    let win_at = |rules: WinRules| {
+      let wins = Game::new(vec![board.clone()], rules).play(&draws);
+      wins.first().map(|win| win.draw)
+    };
+
+    assert_eq!(win_at(WinRules::DIAGONALS), Some(2));
+    assert_eq!(win_at(WinRules::ROWS), Some(4));
+    assert_eq!(win_at(WinRules::COLUMNS), Some(6));
+    assert_eq!(win_at(WinRules::FULL_CARD), Some(8));
+    assert_eq!(win_at(WinRules::default()), Some(4));
+
+    assert_eq!("rows,diagonals".parse::<WinRules>().unwrap(), WinRules::ROWS | WinRules::DIAGONALS);
+    assert!("corners".parse::<WinRules>().is_err());
+  }
 }
@@ -0,0 +1,321 @@
+//! Replaying a game in the terminal, one draw at a time, with every board
+//! shown side by side.
+
+use {
+  super::{BingoBoard, Game, Win, WinRules},
+  anyhow::{anyhow, Error},
+  std::{
+    fmt::Write as _,
+    io::{BufRead, Write},
+    str::FromStr,
+    thread,
+    time::Duration,
+  },
+};
+
+const RESET: &str = "\x1b[0m";
+/// Bold green, for marked cells.
+const MARKED: &str = "\x1b[1;32m";
+/// Bold black on yellow, for the cells of a winning line.
+const WINNING: &str = "\x1b[1;30;43m";
+const CLEAR: &str = "\x1b[2J\x1b[H";
+
+/// How many boards fit across a line before wrapping.
+const LINE_WIDTH: usize = 80;
+/// How many of the most recent draws are listed above the boards.
+const RECENT_DRAWS: usize = 10;
+
+/// A game that can be moved to any point in its draw order.
+pub(crate) struct Replay {
+  boards: Vec<BingoBoard>,
+  draws: Vec<u32>,
+  rules: WinRules,
+  game: Game,
+  /// Every win in the whole game, so we know where the winners are before
+  /// reaching them.
+  wins: Vec<Win>,
+}
+
+impl Replay {
+  pub fn new(boards: Vec<BingoBoard>, draws: Vec<u32>, rules: WinRules) -> Replay {
+    let wins = Game::new(boards.clone(), rules).play(&draws);
+    Replay {
+      game: Game::new(boards.clone(), rules),
+      boards,
+      draws,
+      rules,
+      wins,
+    }
+  }
+
+  /// How many numbers have been drawn so far.
+  pub fn position(&self) -> usize {
+    self.game.draws
+  }
+
+  pub fn len(&self) -> usize {
+    self.draws.len()
+  }
+
+  /// Moves to just after the `n`th draw. Going backwards replays the game
+  /// from the start.
+  pub fn seek(&mut self, n: usize) {
+    let n = n.min(self.len());
+    if n < self.position() {
+      self.game = Game::new(self.boards.clone(), self.rules);
+    }
+    while self.position() < n {
+      self.game.draw(self.draws[self.position()]);
+    }
+  }
+
+  /// The position just after the first board wins.
+  pub fn first_win(&self) -> Option<usize> {
+    self.wins.first().map(|win| win.draw + 1)
+  }
+
+  /// The position just after the last board to win does so.
+  pub fn last_win(&self) -> Option<usize> {
+    self.wins.last().map(|win| win.draw + 1)
+  }
+
+  /// Draws the current state of the game. Without colour, marked cells are
+  /// followed by `*` and winning cells by `#`.
+  pub fn render(&self, colour: bool) -> String {
+    let mut out = String::new();
+    let pos = self.position();
+    match pos.checked_sub(1) {
+      Some(last) => writeln!(out, "Draw {}/{}: {}", pos, self.len(), self.draws[last]).unwrap(),
+      None => writeln!(out, "Draw 0/{}", self.len()).unwrap(),
+    }
+    let recent = &self.draws[pos.saturating_sub(RECENT_DRAWS)..pos];
+    let recent = recent.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    writeln!(out, "Recent: {}", recent.join(", ")).unwrap();
+
+    let Some(width) = self.game.boards.first().map(|b| b.width) else {
+      return out;
+    };
+    // Each cell is a right-aligned number, wide enough for the largest one
+    // with a space before it, and a one character mark.
+    let largest = self
+      .game
+      .boards
+      .iter()
+      .flat_map(|b| (0..b.height).flat_map(move |x| (0..b.width).map(move |y| b[(x, y)])))
+      .max()
+      .unwrap_or(0);
+    let digits = largest.to_string().len().max(2) + 1;
+    let board_width = (width * (digits + 1)).max(16);
+    let per_line = (LINE_WIDTH / (board_width + 2)).max(1);
+
+    let boards = self.game.boards.iter().enumerate().collect::<Vec<_>>();
+    for group in boards.chunks(per_line) {
+      writeln!(out).unwrap();
+      let labels = group
+        .iter()
+        .map(|(i, _)| {
+          let label = match self.wins.iter().find(|w| w.board == *i && w.draw < pos) {
+            Some(win) => format!("Board {} won {}", i, win.draw + 1),
+            None => format!("Board {}", i),
+          };
+          format!("{:<w$}", label, w = board_width)
+        })
+        .collect::<Vec<_>>();
+      writeln!(out, "{}", labels.join("  ").trim_end()).unwrap();
+
+      for x in 0..group[0].1.height {
+        let rows = group
+          .iter()
+          .map(|(_, board)| {
+            let winning = board.winning_cells(self.rules);
+            let mut row = String::new();
+            for y in 0..board.width {
+              let style = if winning.contains(&(x, y)) {
+                Some((WINNING, '#'))
+              } else if board.marked[x * board.width + y] {
+                Some((MARKED, '*'))
+              } else {
+                None
+              };
+              match style {
+                Some((code, _)) if colour => write!(row, "{}{:>d$}{} ", code, board[(x, y)], RESET, d = digits),
+                Some((_, mark)) => write!(row, "{:>d$}{}", board[(x, y)], mark, d = digits),
+                None => write!(row, "{:>d$} ", board[(x, y)], d = digits),
+              }
+              .unwrap();
+            }
+            row + &" ".repeat(board_width - board.width * (digits + 1))
+          })
+          .collect::<Vec<_>>();
+        writeln!(out, "{}", rows.join("  ").trim_end()).unwrap();
+      }
+    }
+    out
+  }
+}
+
+/// A command typed at the replay prompt.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Control {
+  Step,
+  Back,
+  /// Step through the rest of the game without waiting.
+  Run,
+  First,
+  Last,
+  /// Go to just after the given draw.
+  Jump(usize),
+  Quit,
+}
+
+impl FromStr for Control {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    match s.trim() {
+      "" | "s" | "step" => Ok(Control::Step),
+      "b" | "back" => Ok(Control::Back),
+      "r" | "run" => Ok(Control::Run),
+      "f" | "first" => Ok(Control::First),
+      "l" | "last" => Ok(Control::Last),
+      "q" | "quit" => Ok(Control::Quit),
+      other => other
+        .parse()
+        .map(Control::Jump)
+        .map_err(|_| anyhow!("Unknown command: {}", other)),
+    }
+  }
+}
+
+const HELP: &str = "[enter] step, b back, r run, f first winner, l last winner, N go to draw N, q quit";
+
+/// Runs the replay, reading commands from `input` until it runs out or the
+/// user quits. `delay` is the pause between frames when running.
+pub(crate) fn run(
+  replay: &mut Replay,
+  mut input: impl BufRead,
+  mut out: impl Write,
+  colour: bool,
+  delay: Duration,
+) -> Result<(), Error> {
+  let frame = |replay: &Replay, out: &mut dyn Write, message: &str| -> Result<(), Error> {
+    if colour {
+      write!(out, "{}", CLEAR)?;
+    }
+    write!(out, "{}\n{}\n{}> ", replay.render(colour), message, HELP)?;
+    out.flush()?;
+    Ok(())
+  };
+
+  frame(replay, &mut out, "")?;
+  let mut line = String::new();
+  loop {
+    line.clear();
+    if input.read_line(&mut line)? == 0 {
+      writeln!(out)?;
+      return Ok(());
+    }
+
+    let mut message = String::new();
+    match line.parse::<Control>() {
+      Ok(Control::Step) => replay.seek(replay.position() + 1),
+      Ok(Control::Back) => replay.seek(replay.position().saturating_sub(1)),
+      Ok(Control::Run) => {
+        while replay.position() < replay.len() {
+          replay.seek(replay.position() + 1);
+          frame(replay, &mut out, "")?;
+          thread::sleep(delay);
+        }
+      }
+      Ok(Control::First) => match replay.first_win() {
+        Some(pos) => replay.seek(pos),
+        None => message = "No board wins".to_owned(),
+      },
+      Ok(Control::Last) => match replay.last_win() {
+        Some(pos) => replay.seek(pos),
+        None => message = "No board wins".to_owned(),
+      },
+      Ok(Control::Jump(pos)) => replay.seek(pos),
+      Ok(Control::Quit) => return Ok(()),
+      Err(e) => message = e.to_string(),
+    }
+    frame(replay, &mut out, &message)?;
+  }
+}
+
+mod tests {
+  #[test]
+  fn render() {
+    use {
+      super::Replay,
+      crate::{BingoBoard, WinRules},
+    };
+
+    let boards = ["1 2\n3 4", "5 6\n7 8"].map(|b| b.parse::<BingoBoard>().unwrap());
+    let mut replay = Replay::new(boards.to_vec(), vec![5, 1, 2, 8], WinRules::default());
+    assert_eq!((replay.first_win(), replay.last_win()), (Some(3), Some(3)));
+
+    replay.seek(3);
+    assert_eq!(
+      replay.render(false),
+      "Draw 3/4: 2\n\
+       Recent: 5, 1, 2\n\
+       \n\
+       Board 0 won 3     Board 1\n  \
+         1#  2#            5*  6\n  \
+         3   4             7   8\n"
+    );
+
+    let colour = replay.render(true);
+    assert!(colour.contains("\x1b[1;30;43m  1\x1b[0m"));
+    assert!(colour.contains("\x1b[1;32m  5\x1b[0m"));
+
+    // Going backwards replays from the start.
+    replay.seek(1);
+    assert!(replay.render(false).contains("Board 0           Board 1\n  1   2             5*  6\n"));
+
+    // Cells are as wide as the largest number on any board needs.
+    let boards = ["1000 2\n3000 4\n5 6", "7 8\n9 10\n11 12"].map(|b| b.parse::<BingoBoard>().unwrap());
+    let mut replay = Replay::new(boards.to_vec(), vec![3000, 8], WinRules::default());
+    replay.seek(2);
+    assert_eq!(
+      replay.render(false),
+      "Draw 2/2: 8\n\
+       Recent: 3000, 8\n\
+       \n\
+       Board 0           Board 1\n \
+         1000     2           7     8*\n \
+         3000*    4           9    10\n    \
+            5     6          11    12\n"
+    );
+  }
+
+  #[test]
+  fn controls() {
+    use {
+      super::{run, Control, Replay},
+      crate::{parse, WinRules},
+      std::time::Duration,
+    };
+
+    assert_eq!("".parse::<Control>().unwrap(), Control::Step);
+    assert_eq!(" 12 ".parse::<Control>().unwrap(), Control::Jump(12));
+    assert!("x".parse::<Control>().is_err());
+
+    let parse::Input { draws, boards } = parse::input(include_str!("../sample.txt")).unwrap();
+    let mut replay = Replay::new(boards, draws, WinRules::default());
+
+    let mut out = Vec::new();
+    run(&mut replay, "\n\nb\nf\n".as_bytes(), &mut out, false, Duration::ZERO).unwrap();
+    assert_eq!(replay.position(), 12);
+    assert!(String::from_utf8(out).unwrap().contains("Board 2 won 12"));
+
+    run(&mut replay, "l\nq\n5\n".as_bytes(), Vec::new(), false, Duration::ZERO).unwrap();
+    assert_eq!(replay.position(), 15);
+
+    let mut out = Vec::new();
+    run(&mut replay, "1\nhuh\nr\n".as_bytes(), &mut out, false, Duration::ZERO).unwrap();
+    assert_eq!(replay.position(), 27);
+    assert!(String::from_utf8(out).unwrap().contains("Unknown command: huh"));
+  }
+}
@@ -0,0 +1,22 @@
+//! Any input either fails to parse or parses to lines that print back to
+//! something that parses to the same lines.
+
+#![no_main]
+
+use {
+  libfuzzer_sys::fuzz_target,
+  p05_hydrothermal_venture::parse,
+};
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(s) = std::str::from_utf8(data) else {
+    return;
+  };
+  let Ok(lines) = parse::input(s) else {
+    return;
+  };
+
+  let printed = lines.iter().map(|l| l.to_string()).collect::<Vec<_>>().join("\n");
+  let reparsed = parse::input(&printed).unwrap_or_else(|e| panic!("{:?} printed as {:?}: {}", lines, printed, e));
+  assert_eq!(reparsed, lines);
+});
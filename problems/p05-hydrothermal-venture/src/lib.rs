@@ -0,0 +1,328 @@
+//! Points and the vent lines between them, with which points a line covers,
+//! and the parser for a list of lines.
+
+use {
+  anyhow::Error,
+  std::{
+    fmt,
+    ops::{Sub}
+  },
+};
+
+#[derive(Debug, Ord, PartialOrd, PartialEq, Eq, Default, Clone, Copy)]
+pub struct Point {
+  pub x: i64,
+  pub y: i64,
+}
+
+impl Sub for Point {
+  type Output = Self;
+  fn sub(self, rhs: Self) -> Self::Output {
+      Point {
+        x: self.x - rhs.x,
+        y: self.y - rhs.y,
+      }
+  }
+}
+
+impl Point {
+  // Note that the cross product is always in the z direction, so we return it as i64.
+  fn cross(self: Point, rhs: Point) -> i64 {
+    self.x * rhs.y - rhs.x * self.y
+  }
+
+  fn dot(self: Point, rhs: Point) -> i64 {
+    self.x * rhs.x + self.y * rhs.y
+  }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Line {
+  pub start: Point,
+  pub end: Point,
+}
+
+#[derive(PartialEq, Eq, Debug)]
+pub enum Orientation {
+  Horizontal,
+  Vertical,
+}
+
+/// Prints the line the way the input writes it.
+impl fmt::Display for Line {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    write!(f, "{},{} -> {},{}", self.start.x, self.start.y, self.end.x, self.end.y)
+  }
+}
+
+impl Line {
+  pub fn contains(&self, point: Point) -> bool {
+    match self.orientation() {
+      Some(Orientation::Vertical) if self.start.x == point.x => {
+        point.y >= i64::min(self.start.y, self.end.y)
+          && point.y <= i64::max(self.start.y, self.end.y)
+      }
+      Some(Orientation::Horizontal) if self.start.y == point.y => {
+        point.x >= i64::min(self.start.x, self.end.x)
+          && point.x <= i64::max(self.start.x, self.end.x)
+      }
+      _ => false,
+    }
+  }
+
+  pub fn contains2(&self, point: Point) -> bool {
+    // If the line contains the point, then start -> point and point -> end are colinear
+
+    // A line that's a single point has no direction, so everything would look
+    // colinear with it.
+    if self.start == self.end {
+      return point == self.start;
+    }
+
+    let dot = (self.end - self.start).dot(point - self.start);
+
+    let cross = (self.end - self.start).cross(point - self.start);
+
+    cross == 0 && dot >= 0 && dot <= (self.end - self.start).dot(self.end - self.start)
+  }
+
+  pub fn orientation(&self) -> Option<Orientation> {
+    if self.start.y == self.end.y {
+      Some(Orientation::Horizontal)
+    } else if self.start.x == self.end.x {
+      Some(Orientation::Vertical)
+    } else {
+      None
+    }
+  }
+
+  // fn intersects(&self, other: &Line) -> Option<Point> {
+  //   let a1 = self.end.y - self.start.y;
+  //   let b1 = self.start.x - self.end.y;
+  //   let c1 = a1 * self.start.x + b1 * self.start.y;
+
+  //   let a2 = other.end.y - other.start.y;
+  //   let b2 = other.start.x - other.end.y;
+  //   let c2 = a2 * other.start.x + b2 * other.start.y;
+
+  //   let det = a1*b2 - a2 * b1;
+  //   if det == 0 {
+  //     None
+  //   }
+  //   else {
+  //     let p = Point {
+  //       x: (b2 * c1 - b1 * c2) / det,
+  //       y: (a1 * c2 - a2 * c1) / det,
+  //     };
+  //     if p.x >= self.start.x && p.x <= self.end.x && p.x >= other.start.x && p.x <= other.end.x {
+  //       Some(p)
+  //     }
+  //     else {
+  //       None
+  //     }
+  //   }
+  // }
+}
+
+pub mod parse {
+  use {
+    super::*,
+    nom::{
+      bytes::complete::tag,
+      error::{context, VerboseError},
+      sequence::separated_pair,
+      IResult,
+    },
+    util::parse::{each_line, signed},
+  };
+
+  fn dec_value(input: &str) -> IResult<&str, i64, VerboseError<&str>> {
+    context("a number", signed)(input)
+  }
+
+  fn point(i: &str) -> IResult<&str, Point, VerboseError<&str>> {
+    let (input, (x, y)) = separated_pair(dec_value, context("\",\"", tag(",")), dec_value)(i)?;
+
+    Ok((input, Point { x, y }))
+  }
+
+  fn line(i: &str) -> IResult<&str, Line, VerboseError<&str>> {
+    let (input, (start, end)) = separated_pair(point, context("\" -> \"", tag(" -> ")), point)(i)?;
+
+    Ok((input, Line { start, end }))
+  }
+
+  /// Parses one line per row. Every row must be a complete line, though the
+  /// input may end with a newline.
+  pub fn input(s: &str) -> Result<Vec<Line>, Error> {
+    each_line(s, line)
+  }
+} // mod parse
+
+#[cfg(test)]
+mod tests {
+  use {
+    crate::{Line, Point},
+    proptest::prelude::*,
+  };
+
+  #[test]
+  fn test_contains_horizontal() {
+    use super::{Line, Point};
+    let line = Line {
+      start: Point { x: 0, y: 9 },
+      end: Point { x: 5, y: 9 },
+    };
+    let point = Point { x: 2, y: 9 };
+
+    assert_eq!(line.orientation(), Some(crate::Orientation::Horizontal));
+
+    assert_eq!(line.start.y, point.y);
+    assert!(point.y >= i64::min(line.start.y, line.end.y));
+    assert!(point.y <= i64::max(line.start.y, line.end.y));
+
+    assert!(line.contains(point));
+  }
+
+  #[test]
+  fn test_contains_vertical() {
+    use super::{Line, Point};
+
+    let line = Line {
+      start: Point { x: 5, y: 0 },
+      end: Point { x: 5, y: 9 },
+    };
+
+    assert_eq!(line.orientation(), Some(crate::Orientation::Vertical));
+
+    assert!(line.contains(Point { x: 5, y: 4 }));
+  }
+
+  #[test]
+  fn test_contains2() {
+    use super::{Line, Point};
+
+    let line = Line {
+      start: Point { x: 1, y: 1 },
+      end: Point { x: 3, y: 3 },
+    };
+
+    assert_eq!(line.orientation(), None);
+
+    assert!(line.contains2(Point { x: 2, y: 2 }));
+    assert!(!line.contains2(Point { x: 4, y: 4 }));
+
+    let line = Line {
+      start: Point { x: 9, y: 7 },
+      end: Point { x: 7, y: 9 },
+    };
+
+    assert_eq!(line.orientation(), None);
+
+    assert!(line.contains2(Point { x: 8, y: 8 }));
+    assert!(!line.contains2(Point { x: 6, y: 10 }));
+  }
+
+  #[test]
+  fn test_orientation() {
+    use super::{Line, Orientation, Point};
+
+    assert_eq!(
+      Line {
+        start: Point { x: 0, y: 9 },
+        end: Point { x: 5, y: 9 }
+      }
+      .orientation(),
+      Some(Orientation::Horizontal)
+    );
+
+    assert_eq!(
+      Line {
+        start: Point { x: 0, y: 0 },
+        end: Point { x: 0, y: 9 }
+      }
+      .orientation(),
+      Some(Orientation::Vertical)
+    );
+  }
+
+  #[test]
+  fn parse() {
+    use crate::{parse, Point};
+
+    let lines = parse::input(include_str!("../sample.txt")).unwrap();
+    assert_eq!(lines.len(), 10);
+    assert_eq!(lines[9].end, Point { x: 8, y: 2 });
+
+    let lines = parse::input("-1,2 -> 3,-4\r\n0,0 -> 0,0\n").unwrap();
+    assert_eq!((lines[0].start, lines[0].end), (Point { x: -1, y: 2 }, Point { x: 3, y: -4 }));
+    assert_eq!(lines.len(), 2);
+
+    assert!(parse::input("").unwrap().is_empty());
+  }
+
+  #[test]
+  fn round_trip() {
+    use crate::parse;
+
+    let input = include_str!("../sample.txt");
+    let lines = parse::input(input).unwrap();
+    let printed = lines.iter().map(|l| l.to_string()).collect::<Vec<_>>().join("\n");
+    assert_eq!(printed, input.trim_end());
+    assert_eq!(parse::input(&printed).unwrap(), lines);
+  }
+
+  #[test]
+  fn parse_errors() {
+    use crate::parse;
+
+    let err = |s: &str| parse::input(s).err().unwrap().to_string();
+
+    assert_eq!(err("1,2 -> 3\n"), "line 1, column 9: expected \",\"");
+    assert_eq!(err("1,2 -> 3,4\n5,,6 -> 1,1\n"), "line 2, column 3: expected a number");
+    assert_eq!(err("1,2 => 3,4"), "line 1, column 4: expected \" -> \"");
+    assert_eq!(err("1,2 -> 3,4 x\n"), "line 1, column 11: expected end of line");
+    assert_eq!(err("1,2 -> 3,4\n\n5,6 -> 7,8\n"), "line 2, column 1: expected a number");
+    assert_eq!(err("1,2 -> 3,99999999999999999999\n"), "line 1, column 10: expected a number");
+  }
+
+  /// Every point on a horizontal, vertical or 45 degree line.
+  fn rasterise(line: &Line) -> Vec<Point> {
+    let (dx, dy) = ((line.end.x - line.start.x).signum(), (line.end.y - line.start.y).signum());
+    let len = (line.end.x - line.start.x).abs().max((line.end.y - line.start.y).abs());
+    (0..=len)
+      .map(|i| Point {
+        x: line.start.x + i * dx,
+        y: line.start.y + i * dy,
+      })
+      .collect()
+  }
+
+  /// Lines the puzzle can contain, including ones that are a single point.
+  fn line() -> impl Strategy<Value = Line> {
+    (0..20i64, 0..20i64, 0..3, -1..=1i64, -1..=1i64, 0..10i64).prop_map(|(x, y, kind, dx, dy, len)| {
+      let (dx, dy) = match kind {
+        0 => (dx, 0),
+        1 => (0, dy),
+        _ => (dx, dy),
+      };
+      Line {
+        start: Point { x, y },
+        end: Point {
+          x: x + dx * len,
+          y: y + dy * len,
+        },
+      }
+    })
+  }
+
+  proptest! {
+    #[test]
+    fn contains_matches_rasterising(line in line(), x in -2..32i64, y in -2..32i64) {
+      let point = Point { x, y };
+      let on_line = rasterise(&line).contains(&point);
+      prop_assert_eq!(line.contains2(point), on_line);
+      prop_assert_eq!(line.contains(point), on_line && line.orientation().is_some());
+    }
+  }
+}
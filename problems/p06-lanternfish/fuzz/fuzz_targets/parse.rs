@@ -0,0 +1,25 @@
+//! Any input either fails to parse or parses to counts of fish that, printed
+//! back as a list of timers, parse to the same counts.
+
+#![no_main]
+
+use {
+  libfuzzer_sys::fuzz_target,
+  p06_lanternfish::read_input,
+};
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(s) = std::str::from_utf8(data) else {
+    return;
+  };
+  let Ok(counts) = read_input(s, 0) else {
+    return;
+  };
+
+  let printed = (0..9)
+    .flat_map(|timer| std::iter::repeat_n(timer.to_string(), counts[timer]))
+    .collect::<Vec<_>>()
+    .join(",");
+  let reparsed = read_input(&printed, 0).unwrap_or_else(|e| panic!("{:?} printed as {:?}: {}", counts, printed, e));
+  assert_eq!(reparsed, counts);
+});
@@ -0,0 +1,45 @@
+//! Lanternfish counted by timer, rather than one at a time, and the parser
+//! for the list of timers.
+
+use {
+  anyhow::{ensure, Error},
+  util::{error::Diagnostic, parse::csv_spanned},
+};
+
+/// Reads the comma-separated timers, each from 0 to 8, counting the fish
+/// with each timer. Slot 9 is scratch space for `step`.
+pub fn read_input(input: &str, round: usize) -> Result<[usize; 10], Error> {
+  let mut counts: [usize; 10] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
+
+  for (text, timer) in csv_spanned::<usize>(input)? {
+    ensure!(timer <= 8, Diagnostic::spanning(input, text, format!("expected a timer from 0 to 8, got {}", timer)));
+    counts[timer] += 1;
+  }
+
+  counts[0..7].rotate_right(round);
+
+  Ok(counts)
+}
+
+pub fn step(counts: &mut [usize; 10], round: &mut usize) -> [usize; 10] {
+  counts[9] = counts[*round % 7];
+  counts[*round % 7] += counts[7];
+  counts[7] = counts[8];
+  counts[8] = counts[9];
+  counts[9] = 0;
+  *round += 1;
+  *counts
+}
+
+#[cfg(test)]
+mod tests {
+  #[test]
+  fn bad_timers() {
+    let err = |s: &str| crate::read_input(s, 0).unwrap_err().to_string();
+    assert_eq!(err("12,3\n"), "line 1, column 1: expected a timer from 0 to 8, got 12");
+    assert_eq!(err("3, 9"), "line 1, column 4: expected a timer from 0 to 8, got 9");
+    assert_eq!(err("3,-1"), "line 1, column 2: expected comma-separated numbers");
+    assert_eq!(err("3,4\n5"), "line 1, column 4: expected comma-separated numbers");
+    assert_eq!(err("\n"), "line 2, column 1: expected comma-separated numbers");
+  }
+}
@@ -1,51 +1,44 @@
 use {
-  anyhow::{anyhow, Error},
-  std::io::{self},
+  p06_lanternfish::{read_input, step},
+  std::env,
+  tracing::{debug, info_span, trace},
+  util::{
+    answers::{Answers, Options},
+    error::Report,
+    trace,
+  },
 };
 
-fn read_input(input: &str, round: usize) -> Result<[usize; 10], Error> {
-  let mut counts: [usize; 10] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
-
-  for count in input.split(',').map(str::parse::<usize>) {
-    counts[count?] += 1;
-  }
-
-  counts[0..7].rotate_right(round);
-
-  Ok(counts)
-}
-
-fn step(counts: &mut [usize; 10], round: &mut usize) -> [usize; 10] {
-  counts[9] = counts[*round % 7];
-  counts[*round % 7] += counts[7];
-  counts[7] = counts[8];
-  counts[8] = counts[9];
-  counts[9] = 0;
-  *round += 1;
-  *counts
-}
-
-fn main() -> Result<(), Error> {
-  let mut counts = read_input(
-    &io::stdin()
-      .lines()
-      .next()
-      .ok_or(anyhow!("Failed to read input"))??,
-      0
-  )?;
+/// How many fish there are after 80 days, and after 256.
+fn solve(mut counts: [usize; 10]) -> Answers {
   let mut round = 0;
+  let mut after = |days: usize| {
+    while round < days {
+      step(&mut counts, &mut round);
+      trace!("round {} (offset {}): {:?}", round, round % 7, counts);
+    }
+    debug!("round {} (offset {}): {:?}", round, round % 7, counts);
+    counts.iter().sum::<usize>()
+  };
+  let part1 = info_span!("part1").in_scope(|| after(80));
+  let part2 = info_span!("part2").in_scope(|| after(256));
+  Answers::new(part1, part2)
+}
 
-  dbg!(counts);
-  (0..256).for_each(|_| {step(&mut counts, &mut round);});
-
-  dbg!(counts.iter().sum::<usize>());
-
-  println!("round {} (offset {}): {:#?}", round, round % 7, counts);
+fn main() -> Result<(), Report> {
+  let (options, _) = Options::from_args(env::args().skip(1))?;
+  trace::init(options.verbosity)?;
 
-  Ok(())
+  Ok(options.run(|input| {
+    let counts = info_span!("parse").in_scope(|| read_input(input, 0))?;
+    Ok(solve(counts))
+  })?)
 }
 
+#[cfg(test)]
 mod tests {
+  use proptest::prelude::*;
+
   #[test]
   fn sample() {
     let mut actual = crate::read_input("3,4,3,1,2", 0).unwrap();
@@ -70,5 +63,39 @@ mod tests {
     assert_eq!( crate::step(&mut actual, &mut round), crate::read_input("6,0,6,4,5,6,0,1,1,2,6,0,1,1,1,2,2,3,3,4,6,7,8,8,8,8", round % 7).unwrap() );
 
     assert_eq!(actual.iter().sum::<usize>(), 26);
+
+    let counts = crate::read_input("3,4,3,1,2", 0).unwrap();
+    assert_eq!(crate::solve(counts), util::answers::Answers::new(5934, 26984457539u64));
+  }
+
+  #[test]
+  fn generated() {
+    util::generators::check(6, 100, |s| Ok(crate::solve(crate::read_input(s, 0)?)));
+  }
+
+  /// One day for a list of every fish's timer.
+  fn naive_step(fish: &mut Vec<u8>) {
+    let born = fish.iter().filter(|t| **t == 0).count();
+    for timer in fish.iter_mut() {
+      *timer = if *timer == 0 { 6 } else { *timer - 1 };
+    }
+    fish.extend(std::iter::repeat_n(8, born));
+  }
+
+  proptest! {
+    #[test]
+    fn step_matches_naive(mut fish in prop::collection::vec(0..=6u8, 1..30), days in 0..60usize) {
+      let input = fish.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(",");
+      let mut counts = crate::read_input(&input, 0).unwrap();
+      let mut round = 0;
+      for _ in 0..days {
+        crate::step(&mut counts, &mut round);
+        naive_step(&mut fish);
+      }
+
+      let expected = fish.iter().map(|t| t.to_string()).collect::<Vec<_>>().join(",");
+      prop_assert_eq!(counts, crate::read_input(&expected, round % 7).unwrap());
+      prop_assert_eq!(counts.iter().sum::<usize>(), fish.len());
+    }
   }
 }
@@ -0,0 +1,28 @@
+//! Any line either fails to parse or parses to an entry that prints back to
+//! something that parses, in full, to the same entry. The same goes for a
+//! single digit.
+
+#![no_main]
+
+use {
+  libfuzzer_sys::fuzz_target,
+  p08_seven_segment_search::parse,
+};
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(s) = std::str::from_utf8(data) else {
+    return;
+  };
+
+  for line in s.lines() {
+    if let Ok((_, entry)) = parse::input_line(line) {
+      let printed = entry.to_string();
+      assert_eq!(parse::input_line(&printed), Ok(("", entry)), "printed as {:?}", printed);
+    }
+
+    if let Ok((_, digit)) = parse::digit(line) {
+      let printed = digit.to_string();
+      assert_eq!(parse::digit(&printed), Ok(("", digit)), "printed as {:?}", printed);
+    }
+  }
+});
@@ -0,0 +1,212 @@
+//! Segment patterns and the digits they can show, and the parser for the
+//! notes: ten scrambled patterns and four output digits a line.
+
+pub mod parse {
+  use {
+    anyhow::{
+      anyhow,
+      Error,
+    },
+    bitflags::bitflags,
+    nom::{
+      self,
+      bytes::complete::tag,
+      combinator::map_res,
+      error::{context, VerboseError},
+      multi::separated_list0,
+      sequence::separated_pair,
+      IResult,
+    },
+    std::fmt,
+  };
+
+  bitflags! {
+    #[derive(Default)]
+    pub struct Digits : u16 {
+      const ZERO = 1 << 0;
+      const ONE = 1 << 1;
+      const TWO = 1 << 2;
+      const THREE = 1 << 3;
+      const FOUR = 1 << 4;
+      const FIVE = 1 << 5;
+      const SIX = 1 << 6;
+      const SEVEN = 1 << 7;
+      const EIGHT = 1 << 8;
+      const NINE = 1 << 9;
+    }
+  }
+
+  impl From<Digits> for u8 {
+    fn from(digits: Digits) -> u8 {
+      let mut out = 0;
+      if !(digits & Digits::ZERO).is_empty() {
+        out += 0;
+      }
+      if !(digits & Digits::ONE).is_empty() {
+        out += 1;
+      }
+      if !(digits & Digits::TWO).is_empty() {
+        out += 2;
+      }
+      if !(digits & Digits::THREE).is_empty() {
+        out += 3;
+      }
+      if !(digits & Digits::FOUR).is_empty() {
+        out += 4;
+      }
+      if !(digits & Digits::FIVE).is_empty() {
+        out += 5;
+      }
+      if !(digits & Digits::SIX).is_empty() {
+        out += 6;
+      }
+      if !(digits & Digits::SEVEN).is_empty() {
+        out += 7;
+      }
+      if !(digits & Digits::EIGHT).is_empty() {
+        out += 8;
+      }
+      if !(digits & Digits::NINE).is_empty() {
+        out += 9;
+      }
+
+      out
+    }
+  }
+
+  bitflags! {
+    #[derive(Default,)]
+    pub struct Segments: u8 {
+      const A = 1 << 0;
+      const B = 1 << 1;
+      const C = 1 << 2;
+      const D = 1 << 3;
+      const E = 1 << 4;
+      const F = 1 << 5;
+      const G = 1 << 6;
+    }
+  }
+
+  impl Segments {
+    pub fn all_iter() -> impl Iterator<Item = Segments> {
+      [
+        Segments::A,
+        Segments::B,
+        Segments::C,
+        Segments::D,
+        Segments::E,
+        Segments::F,
+        Segments::G,
+      ]
+      .into_iter()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = Segments> {
+      let pred = *self;
+      Segments::all_iter().filter(move |seg| (pred & *seg) != Segments::empty())
+    }
+  }
+
+  /// Prints the segments' letters in order, which parses back to the same
+  /// segments.
+  impl fmt::Display for Segments {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      for (segment, letter) in Segments::all_iter().zip('a'..='g') {
+        if self.contains(segment) {
+          write!(f, "{}", letter)?;
+        }
+      }
+      Ok(())
+    }
+  }
+
+  #[derive(Debug, PartialEq, Eq)]
+  pub struct InputLine {
+    pub vals: [Segments; 10],
+    pub output: [Segments; 4],
+  }
+
+  impl fmt::Display for InputLine {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+      let join = |digits: &[Segments]| digits.iter().map(|d| d.to_string()).collect::<Vec<_>>().join(" ");
+      write!(f, "{} | {}", join(&self.vals), join(&self.output))
+    }
+  }
+
+  pub fn single_segment(chr: char) -> Result<Segments, Error> {
+    match chr {
+      'a' => Ok(Segments::A),
+      'b' => Ok(Segments::B),
+      'c' => Ok(Segments::C),
+      'd' => Ok(Segments::D),
+      'e' => Ok(Segments::E),
+      'f' => Ok(Segments::F),
+      'g' => Ok(Segments::G),
+      _ => Err(anyhow!("Not a valid digit: {}", chr)),
+    }
+  }
+
+  pub fn digit(chrs: &str) -> IResult<&str, Segments, VerboseError<&str>> {
+    context(
+      "segments a to g",
+      nom::multi::fold_many1(
+        map_res(nom::character::complete::one_of("abcdefg"), single_segment),
+        Segments::default,
+        |a, b| -> Segments { a | b },
+      ),
+    )(chrs)
+  }
+
+  pub fn input_line(input: &str) -> IResult<&str, InputLine, VerboseError<&str>> {
+    context(
+      "ten patterns, then \" | \" and four digits",
+      map_res(
+        separated_pair(
+          separated_list0(tag(" "), digit),
+          context("\" | \"", tag(" | ")),
+          separated_list0(tag(" "), digit),
+        ),
+        |(left, right)| -> Result<InputLine, Error> {
+          Ok(InputLine {
+            vals: left.as_slice().try_into()?,
+            output: right.as_slice().try_into()?,
+          })
+        },
+      ),
+    )(input)
+  }
+} // mod parse
+
+mod tests {
+  #[test]
+  fn parse_segment() {
+    assert_eq!(
+      crate::parse::single_segment('a').unwrap(),
+      crate::parse::Segments::A
+    )
+  }
+
+  #[test]
+  fn parse_digit() {
+    assert_eq!(
+      crate::parse::digit("ab").unwrap(),
+      ("", crate::parse::Segments::A | crate::parse::Segments::B)
+    )
+  }
+
+  #[test]
+  fn round_trip() {
+    use crate::parse;
+
+    for line in include_str!("../sample.txt").lines().chain(include_str!("../parse_test.txt").lines()) {
+      let (rest, parsed) = parse::input_line(line).unwrap();
+      assert_eq!(rest, "");
+      let printed = parsed.to_string();
+      assert_eq!(parse::input_line(&printed).unwrap(), ("", parsed));
+    }
+
+    // Segments are printed in order, whatever order they were given in.
+    let (_, parsed) = parse::input_line("ga b c d e f g ab cd ef | a b c dc").unwrap();
+    assert_eq!(parsed.to_string(), "ag b c d e f g ab cd ef | a b c cd");
+  }
+}
@@ -1,186 +1,114 @@
-#![feature(generators, generator_trait, iter_from_generator)]
-
 use {
-  anyhow::{
-    anyhow,
-    Error,
-  },
+  anyhow::{anyhow, Context, Error},
   std::{
     collections::BTreeMap,
-    io,
+    env,
+  },
+  tracing::{debug, info_span, trace},
+  util::{
+    answers::{Answers, Options},
+    error::Report,
+    parse::each_line,
+    trace,
   },
 };
 
-mod parse {
-  use {
-    anyhow::{
-      anyhow,
-      Error,
-    },
-    bitflags::bitflags,
-    nom::{
-      self,
-      bytes::complete::{
-        tag,
-        take_while,
-      },
-      combinator::map_res,
-      multi::separated_list0,
-      sequence::separated_pair,
-      IResult,
-    },
-  };
-
-  bitflags! {
-    #[derive(Default)]
-    pub (crate) struct Digits : u16 {
-      const ZERO = 1 << 0;
-      const ONE = 1 << 1;
-      const TWO = 1 << 2;
-      const THREE = 1 << 3;
-      const FOUR = 1 << 4;
-      const FIVE = 1 << 5;
-      const SIX = 1 << 6;
-      const SEVEN = 1 << 7;
-      const EIGHT = 1 << 8;
-      const NINE = 1 << 9;
-    }
-  }
+use p08_seven_segment_search::parse::{
+  self,
+  Digits,
+  InputLine,
+  Segments,
+};
 
-  impl Into<u8> for Digits {
-    fn into(self) -> u8 {
-      let mut out = 0;
-      if !(self & Digits::ZERO).is_empty() {
-        out += 0;
+/// Works out which segments make which digit from a line's ten patterns,
+/// then reads the line's output with them.
+fn decode(line: &InputLine) -> Result<usize, Error> {
+  let mut mapping = BTreeMap::<Segments, Digits>::default();
+  let mut revmap = BTreeMap::<Digits, Segments>::default();
+  for segments in line.vals.iter() {
+    let entry = mapping.entry(*segments).or_insert(Digits::all());
+    match segments.iter().count() {
+      2 => {
+        *entry = Digits::ONE;
+        revmap.insert(Digits::ONE, *segments);
       }
-      if !(self & Digits::ONE).is_empty() {
-        out += 1;
+      3 => {
+        *entry = Digits::SEVEN;
+        revmap.insert(Digits::SEVEN, *segments);
       }
-      if !(self & Digits::TWO).is_empty() {
-        out += 2;
+      4 => {
+        *entry = Digits::FOUR;
+        revmap.insert(Digits::FOUR, *segments);
       }
-      if !(self & Digits::THREE).is_empty() {
-        out += 3;
+      7 => {
+        *entry = Digits::EIGHT;
+        revmap.insert(Digits::EIGHT, *segments);
       }
-      if !(self & Digits::FOUR).is_empty() {
-        out += 4;
+      _ => {
+        continue;
       }
-      if !(self & Digits::FIVE).is_empty() {
-        out += 5;
+    }
+  }
+
+  // The 1 and the 4 tell the five and six segment digits apart.
+  let known = |digit: Digits| {
+    revmap
+      .get(&digit)
+      .copied()
+      .ok_or_else(|| anyhow!("no pattern for a {}", u8::from(digit)))
+  };
+  let (one, four) = (known(Digits::ONE)?, known(Digits::FOUR)?);
+  for segments in line.vals.iter() {
+    let entry = mapping.entry(*segments).or_insert(Digits::all());
+    match segments.iter().count() {
+      5 if *segments & one == one => {
+        *entry = Digits::THREE;
+        revmap.insert(Digits::THREE, *segments);
       }
-      if !(self & Digits::SIX).is_empty() {
-        out += 6;
+      5 if (*segments & four).iter().count() == 2 => {
+        *entry = Digits::TWO;
+        revmap.insert(Digits::TWO, *segments);
       }
-      if !(self & Digits::SEVEN).is_empty() {
-        out += 7;
+      5 => {
+        *entry = Digits::FIVE;
+        revmap.insert(Digits::FIVE, *segments);
       }
-      if !(self & Digits::EIGHT).is_empty() {
-        out += 8;
+      6 if *segments & four == four => {
+        *entry = Digits::NINE;
+        revmap.insert(Digits::NINE, *segments);
       }
-      if !(self & Digits::NINE).is_empty() {
-        out += 9;
+      6 if *segments & one == one => {
+        *entry = Digits::ZERO;
+        revmap.insert(Digits::ZERO, *segments);
       }
-
-      out
-    }
-  }
-
-  bitflags! {
-    #[derive(Default,)]
-    pub (crate) struct Segments: u8 {
-      const A = 1 << 0;
-      const B = 1 << 1;
-      const C = 1 << 2;
-      const D = 1 << 3;
-      const E = 1 << 4;
-      const F = 1 << 5;
-      const G = 1 << 6;
-    }
-  }
-
-  impl Segments {
-    pub fn all_iter() -> impl Iterator<Item = Segments> {
-      std::iter::from_generator(|| {
-        yield Segments::A;
-        yield Segments::B;
-        yield Segments::C;
-        yield Segments::D;
-        yield Segments::E;
-        yield Segments::F;
-        yield Segments::G;
-      })
-    }
-
-    pub fn iter(&self) -> impl Iterator<Item = Segments> {
-      let pred = *self;
-      Segments::all_iter().filter(move |seg| (pred & *seg) != Segments::empty())
-    }
-  }
-
-  #[derive(Debug)]
-  pub(crate) struct InputLine {
-    pub vals: [Segments; 10],
-    pub output: [Segments; 4],
-  }
-
-  pub(crate) fn single_segment(chr: char) -> Result<Segments, Error> {
-    match chr {
-      'a' => Ok(Segments::A),
-      'b' => Ok(Segments::B),
-      'c' => Ok(Segments::C),
-      'd' => Ok(Segments::D),
-      'e' => Ok(Segments::E),
-      'f' => Ok(Segments::F),
-      'g' => Ok(Segments::G),
-      _ => Err(anyhow!("Not a valid digit: {}", chr)),
+      6 => {
+        *entry = Digits::SIX;
+        revmap.insert(Digits::SIX, *segments);
+      }
+      2 | 3 | 4 | 7 => continue,
+      n => return Err(anyhow!("no digit has {} segments, as {} does", n, segments)),
     }
   }
 
-  pub(crate) fn digit(chrs: &str) -> IResult<&str, Segments> {
-    nom::multi::fold_many1(
-      map_res(nom::character::complete::one_of("abcdefg"), single_segment),
-      Segments::default,
-      |a, b| -> Segments { a | b },
-    )(chrs)
+  for (segments, digit) in mapping.iter() {
+    trace!("mapping resolved digit {} to {:?}", u8::from(*digit), segments);
   }
 
-  pub(crate) fn input_line(input: &str) -> IResult<&str, InputLine> {
-    map_res(
-      separated_pair(
-        separated_list0(tag(" "), digit),
-        tag(" | "),
-        separated_list0(tag(" "), digit),
-      ),
-      |(left, right)| -> Result<InputLine, Error> {
-        Ok(InputLine {
-          vals: left.as_slice().try_into()?,
-          output: right.as_slice().try_into()?,
-        })
-      },
-    )(input)
+  let mut out = 0;
+  for segments in line.output.iter() {
+    let digit = mapping
+      .get(segments)
+      .ok_or_else(|| anyhow!("output {} isn't one of the ten patterns", segments))?;
+    out = out * 10 + u8::from(*digit) as usize;
   }
+  Ok(out)
 }
 
-use parse::{
-  Digits,
-  InputLine,
-  Segments,
-};
-
-fn main() -> Result<(), Error> {
-  let lines = io::stdin()
-    .lines()
-    .map(|line| -> Result<parse::InputLine, Error> {
-      Ok(
-        parse::input_line(&line?)
-          .map_err(|e| anyhow!("Parsing failed: {}", e))?
-          .1,
-      )
-    })
-    .collect::<Result<Vec<_>, Error>>()?;
-
+/// Counts the 1s, 4s, 7s and 8s in the outputs, then works out which
+/// segments are which on every line and adds up the outputs.
+fn solve(lines: &[InputLine]) -> Result<Answers, Error> {
   // Part 1
-  {
+  let part1 = info_span!("part1").in_scope(|| {
     let mut counts: [i32; 10] = [0, 0, 0, 0, 0, 0, 0, 0, 0, 0];
     for line in lines.iter() {
       for digit in line.output {
@@ -195,116 +123,76 @@ fn main() -> Result<(), Error> {
         }
       }
     }
-    dbg!(counts.iter().sum::<i32>());
-  }
+    counts.iter().sum::<i32>()
+  });
 
   // Part 2?
-  {
-    let mut counts: BTreeMap<Digits, u32> = Default::default();
-    let result = lines.iter().map(|line| {
-      let mut mapping = BTreeMap::<Segments, Digits>::default();
-      let mut revmap = BTreeMap::<Digits, Segments>::default();
-      for segments in line.vals.iter() {
-        let entry = mapping.entry(*segments).or_insert(Digits::all());
-        match segments.iter().count() {
-          2 => {
-            *entry = Digits::ONE;
-            revmap.insert(Digits::ONE, *segments);
-          }
-          3 => {
-            *entry = Digits::SEVEN;
-            revmap.insert(Digits::SEVEN, *segments);
-          }
-          4 => {
-            *entry = Digits::FOUR;
-            revmap.insert(Digits::FOUR, *segments);
-          }
-          7 => {
-            *entry = Digits::EIGHT;
-            revmap.insert(Digits::EIGHT, *segments);
-          }
-          _ => {
-            continue;
-          }
-        }
-      }
-      for segments in line.vals.iter() {
-        let entry = mapping.entry(*segments).or_insert(Digits::all());
-        match segments.iter().count() {
-          5 if *segments & revmap[&Digits::ONE] == revmap[&Digits::ONE] => {
-            *entry = Digits::THREE;
-            revmap.insert(Digits::THREE, *segments);
-          }
-          5 if (*segments & revmap[&Digits::FOUR]).iter().count() == 2 => {
-            *entry = Digits::TWO;
-            revmap.insert(Digits::TWO, *segments);
-          }
-          5 => {
-            *entry = Digits::FIVE;
-            revmap.insert(Digits::FIVE, *segments);
-          }
-          6 if *segments & revmap[&Digits::FOUR] == revmap[&Digits::FOUR] => {
-            *entry = Digits::NINE;
-            revmap.insert(Digits::NINE, *segments);
-          }
-          6 if *segments & revmap[&Digits::ONE] == revmap[&Digits::ONE] => {
-            *entry = Digits::ZERO;
-            revmap.insert(Digits::ZERO, *segments);
-          }
-          6 => {
-            *entry = Digits::SIX;
-            revmap.insert(Digits::SIX, *segments);
-          }
-          2 | 3 | 4 | 7 => continue,
-          _ => panic!("Unexpected number of segments set"),
-        }
-      }
+  let part2 = info_span!("part2").in_scope(|| {
+    let mut sum = 0;
+    for (i, line) in lines.iter().enumerate() {
+      let out = info_span!("line", number = i + 1)
+        .in_scope(|| decode(line))
+        .with_context(|| format!("Couldn't decode line {}", i + 1))?;
+      debug!("Line {}: output is {}", i + 1, out);
+      sum += out;
+    }
+    Ok::<_, Error>(sum)
+  })?;
 
-      let out = line.output.iter()
-        .rev()
-        .map(|segments| -> u8 { mapping[segments].into() })
-        .enumerate()
-        .map(|(i, d)| d as usize * usize::pow(10, i as u32))
-        .sum::<usize>();
+  Ok(Answers::new(part1, part2))
+}
 
-      dbg!(out)
-    }).sum::<usize>();
-    dbg!(result);
-  }
+fn main() -> Result<(), Report> {
+  let (options, _) = Options::from_args(env::args().skip(1))?;
+  trace::init(options.verbosity)?;
 
-  Ok(())
+  Ok(options.run(|input| {
+    let lines = info_span!("parse").in_scope(|| each_line(input, parse::input_line))?;
+    solve(&lines)
+  })?)
 }
 
 mod tests {
-
-  use {
-    anyhow::Error,
-    nom::{
-      self,
-      bytes::complete::{
-        tag,
-        take_while,
-      },
-      combinator::map_res,
-      multi::separated_list0,
-      sequence::separated_pair,
-      IResult,
-    },
-  };
+  #[test]
+  fn sample() {
+    use {
+      p08_seven_segment_search::parse,
+      util::{answers::Answers, parse::each_line},
+    };
+
+    let lines = each_line(include_str!("../sample.txt"), parse::input_line).unwrap();
+    assert_eq!(crate::solve(&lines).unwrap(), Answers::new(26, 61229));
+  }
 
   #[test]
-  fn parse_segment() {
-    assert_eq!(
-      crate::parse::single_segment('a').unwrap(),
-      crate::parse::Segments::A
-    )
+  fn generated() {
+    use {p08_seven_segment_search::parse, util::parse::each_line};
+
+    util::generators::check(8, 100, |s| crate::solve(&each_line(s, parse::input_line)?));
   }
 
   #[test]
-  fn parse_digit() {
+  fn undecodable() {
+    use {
+      p08_seven_segment_search::parse,
+      util::parse::each_line,
+    };
+
+    let err = |s: &str| {
+      let lines = each_line(s, parse::input_line).unwrap();
+      format!("{:#}", crate::solve(&lines).unwrap_err())
+    };
+    assert_eq!(
+      err("a b c d e f g ab cd ef | a b c cd\n"),
+      "Couldn't decode line 1: no pattern for a 4"
+    );
+    assert_eq!(
+      err("ab abcd a abc abcdefg bcdef abcdf abcef abcdeg abcefg | ab a ab ab\n"),
+      "Couldn't decode line 1: no digit has 1 segments, as a does"
+    );
     assert_eq!(
-      crate::parse::digit("ab").unwrap(),
-      ("", crate::parse::Segments::A | crate::parse::Segments::B)
-    )
+      err("ab abcd abcde abc abcdefg bcdef abcdf abcef abcdeg abcefg | ab g ab ab\n"),
+      "Couldn't decode line 1: output g isn't one of the ten patterns"
+    );
   }
 }
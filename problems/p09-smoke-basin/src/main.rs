@@ -1,16 +1,20 @@
 use {
-  anyhow::{
-    anyhow,
-    Error,
-  },
+  anyhow::Error,
   std::{
-    io,
+    env,
     ops,
-    collections::{BTreeSet, VecDeque}
+    collections::{BTreeSet, VecDeque},
+    str::FromStr,
+  },
+  tracing::{debug, info, info_span},
+  util::{
+    answers::{Answers, Options},
+    error::Report,
+    parse, trace,
   },
 };
 
-#[derive(Default)]
+#[derive(Default, Debug)]
 struct Heightmap {
   width: usize,
   height: usize,
@@ -18,30 +22,40 @@ struct Heightmap {
 }
 
 impl Heightmap {
-  fn from_stdin() -> Result<Heightmap, Error> {
-    let mut result = Heightmap::default();
-    for line in io::stdin().lines() {
-      let line = line?;
-      result.width = line.len();
-      result.height += 1;
-      result.map.extend(line.bytes().map(|b| {
-        match b {
-          b'0' => 0,
-          b'1' => 1,
-          b'2' => 2,
-          b'3' => 3,
-          b'4' => 4,
-          b'5' => 5,
-          b'6' => 6,
-          b'7' => 7,
-          b'8' => 8,
-          b'9' => 9,
-          _ => panic!("Unexpected input"),
+  /// Every point lower than all of its neighbours.
+  fn low_points(&self) -> Vec<(usize, usize)> {
+    let mut points = Vec::new();
+    for y in 0..self.height {
+      for x in 0..self.width {
+        if x > 0 && self[(x - 1, y)] <= self[(x, y)] {
+          continue;
+        }
+        if y > 0 && self[(x, y - 1)] <= self[(x, y)] {
+          continue;
+        }
+        if x < self.width - 1 && self[(x + 1, y)] <= self[(x, y)] {
+          continue;
         }
-      }));
+        if y < self.height - 1 && self[(x, y + 1)] <= self[(x, y)] {
+          continue;
+        }
+        points.push((x, y));
+      }
     }
+    points
+  }
+}
+
+impl FromStr for Heightmap {
+  type Err = Error;
 
-    Ok(result)
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let grid = parse::digit_grid(s)?;
+    Ok(Heightmap {
+      width: grid.width,
+      height: grid.height,
+      map: grid.cells,
+    })
   }
 }
 
@@ -57,76 +71,97 @@ impl ops::IndexMut<(usize, usize)> for Heightmap {
   }
 }
 
-fn main() -> Result<(), Error> {
-  let map = Heightmap::from_stdin()?;
-
-  dbg!(map.width);
-  dbg!(map.height);
-
+/// The total risk level of the low points, and the product of the sizes of
+/// the three biggest basins.
+fn solve(map: &Heightmap) -> Answers {
   // Part 1
-  {
+  let part1 = info_span!("part1").in_scope(|| {
     let mut risk_sum = 0usize;
-    for y in 0..map.height {
-      for x in 0..map.width {
-        if x > 0 && map[(x - 1, y)] <= map[(x, y)] {
-          continue;
-        }
-        if y > 0 && map[(x, y - 1)] <= map[(x, y)] {
-          continue;
-        }
-        if x < map.width - 1 && map[(x + 1, y)] <= map[(x, y)] {
-          continue;
-        }
-        if y < map.height - 1 && map[(x, y + 1)] <= map[(x, y)] {
-          continue;
-        }
-        risk_sum += map[(x, y)] as usize + 1;
-      }
+    for (x, y) in map.low_points() {
+      risk_sum += map[(x, y)] as usize + 1;
     }
 
-    dbg!(risk_sum);
-  }
+    risk_sum
+  });
 
   // Part 2
-  {
+  let part2 = info_span!("part2").in_scope(|| {
     let mut sizes = [0, 0, 0, 0];
-    for y in 0..map.height {
-      for x in 0..map.width {
-        if x > 0 && map[(x - 1, y)] <= map[(x, y)] {
-          continue;
-        }
-        if y > 0 && map[(x, y - 1)] <= map[(x, y)] {
-          continue;
-        }
-        if x < map.width - 1 && map[(x + 1, y)] <= map[(x, y)] {
-          continue;
-        }
-        if y < map.height - 1 && map[(x, y + 1)] <= map[(x, y)] {
-          continue;
-        }
-        
-        // We found a low point
-        // Begin searching outward
-        let mut visited: BTreeSet<(usize, usize)> = Default::default();
-        let mut q = VecDeque::from([(x, y)]);
-        let mut size = 0;
-        while let Some((x, y)) = q.pop_front() {
-          if visited.contains(&(x, y)) {continue};
-          visited.insert((x, y));
-          if map[(x, y)] >= 9 { continue; }
-          if x > 0 { q.push_back((x-1, y)); }
-          if y > 0 { q.push_back((x, y-1)); }
-          if x < map.width-1 { q.push_back((x+1, y)); }
-          if y < map.height-1 { q.push_back((x, y+1)); }
-          size += 1;
-        }
-        sizes[3] = size;
-        sizes.sort();
-        sizes.reverse();
+    for (x, y) in map.low_points() {
+      // Begin searching outward from the low point
+      let mut visited: BTreeSet<(usize, usize)> = Default::default();
+      let mut q = VecDeque::from([(x, y)]);
+      let mut size = 0;
+      while let Some((x, y)) = q.pop_front() {
+        if visited.contains(&(x, y)) {continue};
+        visited.insert((x, y));
+        if map[(x, y)] >= 9 { continue; }
+        if x > 0 { q.push_back((x-1, y)); }
+        if y > 0 { q.push_back((x, y-1)); }
+        if x < map.width-1 { q.push_back((x+1, y)); }
+        if y < map.height-1 { q.push_back((x, y+1)); }
+        size += 1;
       }
+      debug!("Basin found at {:?} with size {}", (x, y), size);
+      sizes[3] = size;
+      sizes.sort();
+      sizes.reverse();
     }
-    dbg!(&sizes[0..3].iter().product::<i32>());
+    sizes[0..3].iter().product::<i32>()
+  });
+
+  Answers::new(part1, part2)
+}
+
+fn main() -> Result<(), Report> {
+  let (options, _) = Options::from_args(env::args().skip(1))?;
+  trace::init(options.verbosity)?;
+
+  Ok(options.run(|input| {
+    let map: Heightmap = info_span!("parse").in_scope(|| input.parse())?;
+    info!("{}x{} heightmap", map.width, map.height);
+    Ok(solve(&map))
+  })?)
+}
+
+#[cfg(test)]
+mod tests {
+  use {crate::Heightmap, proptest::prelude::*};
+
+  #[test]
+  fn sample() {
+    let map: crate::Heightmap = include_str!("../sample.txt").parse().unwrap();
+    assert_eq!(map.low_points(), vec![(1, 0), (9, 0), (2, 2), (6, 4)]);
+    assert_eq!(crate::solve(&map), util::answers::Answers::new(15, 1134));
+  }
+
+  #[test]
+  fn generated() {
+    util::generators::check(9, 100, |s| Ok(crate::solve(&s.parse()?)));
   }
 
-  Ok(())
+  fn heightmap() -> impl Strategy<Value = Heightmap> {
+    (1..12usize, 1..12usize).prop_flat_map(|(width, height)| {
+      prop::collection::vec(0..=9u8, width * height).prop_map(move |map| Heightmap { width, height, map })
+    })
+  }
+
+  proptest! {
+    #[test]
+    fn low_points_match_exhaustive(map in heightmap()) {
+      let mut expected = Vec::new();
+      for y in 0..map.height {
+        for x in 0..map.width {
+          let neighbours = [(-1, 0), (1, 0), (0, -1), (0, 1)]
+            .iter()
+            .filter_map(|(dx, dy)| Some((x.checked_add_signed(*dx)?, y.checked_add_signed(*dy)?)))
+            .filter(|(nx, ny)| *nx < map.width && *ny < map.height);
+          if neighbours.clone().all(|n| map[n] > map[(x, y)]) {
+            expected.push((x, y));
+          }
+        }
+      }
+      prop_assert_eq!(map.low_points(), expected);
+    }
+  }
 }
@@ -0,0 +1,28 @@
+//! Any input either fails to parse, with an error that renders, or parses to
+//! lines of bits, all the same width, that print back to the same bits.
+
+#![no_main]
+
+use {
+  libfuzzer_sys::fuzz_target,
+  util::{error::Report, parse},
+};
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(s) = std::str::from_utf8(data) else {
+    return;
+  };
+
+  match parse::binary(s) {
+    Ok(rows) => {
+      let width = rows.first().map_or(0, Vec::len);
+      assert!(rows.iter().all(|row| row.len() == width && width > 0));
+      let printed = rows
+        .iter()
+        .map(|row| row.iter().map(|bit| if *bit { '1' } else { '0' }).collect::<String>() + "\n")
+        .collect::<String>();
+      assert_eq!(parse::binary(&printed).unwrap(), rows, "printed as {:?}", printed);
+    }
+    Err(e) => drop(format!("{:?}", Report(e))),
+  }
+});
@@ -0,0 +1,30 @@
+//! Blocks are slices of the input that start and end with a line that isn't
+//! blank and hold no blank lines, and between them they hold every line that
+//! isn't blank, in order. Carriage returns at the ends of lines don't count,
+//! even without a newline after them.
+
+#![no_main]
+
+use {
+  libfuzzer_sys::fuzz_target,
+  util::parse,
+};
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(s) = std::str::from_utf8(data) else {
+    return;
+  };
+
+  let blank = |line: &str| line.trim().is_empty();
+  let line_text = |line: &str| line.trim_end_matches('\r').to_owned();
+  let mut lines = Vec::new();
+  for block in parse::blocks(s) {
+    parse::position(s, block);
+    assert!(!block.is_empty() && !block.ends_with('\n'), "{:?}", block);
+    for line in block.lines() {
+      assert!(!blank(line), "blank line in {:?}", block);
+      lines.push(line_text(line));
+    }
+  }
+  assert_eq!(lines, s.lines().filter(|line| !blank(line)).map(line_text).collect::<Vec<_>>());
+});
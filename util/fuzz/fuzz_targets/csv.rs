@@ -0,0 +1,30 @@
+//! Any input either fails to parse, with an error that renders, or parses
+//! to numbers that print back, separated by commas, to the same numbers. Each
+//! number's text is part of the input and says the same number.
+
+#![no_main]
+
+use {
+  libfuzzer_sys::fuzz_target,
+  util::{error::Report, parse},
+};
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(s) = std::str::from_utf8(data) else {
+    return;
+  };
+
+  match parse::csv_spanned::<i64>(s) {
+    Ok(spanned) => {
+      for (text, n) in &spanned {
+        parse::position(s, text);
+        assert_eq!(text.parse::<i64>().ok(), Some(*n));
+      }
+      let nums = spanned.into_iter().map(|(_, n)| n).collect::<Vec<_>>();
+      let printed = nums.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(",");
+      assert_eq!(parse::csv::<i64>(&printed).unwrap(), nums, "printed as {:?}", printed);
+      assert_eq!(parse::csv::<i64>(s).unwrap(), nums);
+    }
+    Err(e) => drop(format!("{:?}", Report(e))),
+  }
+});
@@ -0,0 +1,29 @@
+//! Any input either fails to parse, with an error that renders, or parses to
+//! a rectangle of digits that prints back to the same grid.
+
+#![no_main]
+
+use {
+  libfuzzer_sys::fuzz_target,
+  util::{error::Report, parse},
+};
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(s) = std::str::from_utf8(data) else {
+    return;
+  };
+
+  match parse::digit_grid(s) {
+    Ok(grid) => {
+      assert_eq!(grid.cells.len(), grid.width * grid.height);
+      assert!(grid.cells.iter().all(|d| *d < 10));
+      let printed = grid
+        .cells
+        .chunks(grid.width.max(1))
+        .map(|row| row.iter().map(|d| format!("{}", d)).collect::<String>() + "\n")
+        .collect::<String>();
+      assert_eq!(parse::digit_grid(&printed).unwrap(), grid, "printed as {:?}", printed);
+    }
+    Err(e) => drop(format!("{:?}", Report(e))),
+  }
+});
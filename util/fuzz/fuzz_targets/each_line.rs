@@ -0,0 +1,26 @@
+//! Any input either fails to parse as lines of `x,y` pairs, with an error
+//! that renders, or gives a pair per line that prints back to the same pairs.
+
+#![no_main]
+
+use {
+  libfuzzer_sys::fuzz_target,
+  nom::{character::complete::char, sequence::separated_pair},
+  util::{error::Report, parse},
+};
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(s) = std::str::from_utf8(data) else {
+    return;
+  };
+
+  let pair = |i| separated_pair(parse::signed::<i32, _>, char(','), parse::signed::<i32, _>)(i);
+  match parse::each_line(s, pair) {
+    Ok(pairs) => {
+      assert_eq!(pairs.len(), s.lines().count());
+      let printed = pairs.iter().map(|(x, y)| format!("{},{}\n", x, y)).collect::<String>();
+      assert_eq!(parse::each_line(&printed, pair).unwrap(), pairs, "printed as {:?}", printed);
+    }
+    Err(e) => drop(format!("{:?}", Report(e))),
+  }
+});
@@ -0,0 +1,23 @@
+//! Any input either fails to parse, with an error that renders, or parses
+//! to numbers that print back, one per line, to the same numbers.
+
+#![no_main]
+
+use {
+  libfuzzer_sys::fuzz_target,
+  util::{error::Report, parse},
+};
+
+fuzz_target!(|data: &[u8]| {
+  let Ok(s) = std::str::from_utf8(data) else {
+    return;
+  };
+
+  match parse::values::<i64>(s) {
+    Ok(nums) => {
+      let printed = nums.iter().map(|n| format!("{}\n", n)).collect::<String>();
+      assert_eq!(parse::values::<i64>(&printed).unwrap(), nums, "printed as {:?}", printed);
+    }
+    Err(e) => drop(format!("{:?}", Report(e))),
+  }
+});
@@ -0,0 +1,205 @@
+//! What every day's binary prints: the answers to both parts, as text or as
+//! JSON, with anything else logged through `tracing` (see `trace`).
+
+use {
+  crate::batch,
+  anyhow::{anyhow, Error},
+  std::{
+    fmt,
+    io::{self, Read},
+    str::FromStr,
+  },
+};
+
+/// The answers to a day's puzzle. Either part can be missing, e.g. if the
+/// input doesn't have one.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Answers {
+  pub part1: Option<String>,
+  pub part2: Option<String>,
+}
+
+impl Answers {
+  pub fn new(part1: impl fmt::Display, part2: impl fmt::Display) -> Answers {
+    Answers {
+      part1: Some(part1.to_string()),
+      part2: Some(part2.to_string()),
+    }
+  }
+
+  /// `{"part1": ..., "part2": ...}`, with answers that are integers written
+  /// as JSON numbers however big they are, and missing answers as `null`.
+  pub fn to_json(&self) -> String {
+    let value = |answer: &Option<String>| match answer.as_deref() {
+      None => "null".to_owned(),
+      Some(n) if is_integer(n) => n.to_owned(),
+      Some(s) => serde_json::to_string(s).unwrap(),
+    };
+    format!("{{\"part1\": {}, \"part2\": {}}}", value(&self.part1), value(&self.part2))
+  }
+}
+
+/// Whether `s` is an integer as JSON writes them.
+fn is_integer(s: &str) -> bool {
+  let digits = s.strip_prefix('-').unwrap_or(s);
+  !digits.is_empty() && digits.bytes().all(|b| b.is_ascii_digit()) && (digits == "0" || !digits.starts_with('0'))
+}
+
+/// One line per part that has an answer.
+impl fmt::Display for Answers {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    for (part, answer) in [(1, &self.part1), (2, &self.part2)] {
+      if let Some(answer) = answer {
+        writeln!(f, "part {}: {}", part, answer)?;
+      }
+    }
+    Ok(())
+  }
+}
+
+/// Reads answers back in the form they're printed, ignoring blank lines.
+impl FromStr for Answers {
+  type Err = Error;
+
+  fn from_str(s: &str) -> Result<Self, Self::Err> {
+    let mut answers = Answers::default();
+    for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+      let (part, answer) = line
+        .split_once(':')
+        .ok_or_else(|| anyhow!("Expected \"part N: answer\", got {:?}", line))?;
+      let answer = Some(answer.trim().to_owned());
+      match part.trim() {
+        "part 1" => answers.part1 = answer,
+        "part 2" => answers.part2 = answer,
+        other => return Err(anyhow!("Expected part 1 or part 2, got {:?}", other)),
+      }
+    }
+    Ok(answers)
+  }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+  #[default]
+  Text,
+  Json,
+}
+
+/// The options every day's binary takes.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Options {
+  pub format: Format,
+  /// How many times `-v` was given, which sets how much of the solver's
+  /// workings are logged to stderr.
+  pub verbosity: u8,
+  /// A directory or glob of inputs to solve, instead of stdin. See `batch`.
+  pub batch: Option<String>,
+}
+
+impl Options {
+  /// Takes `--format text|json`, `--batch PATH` and any `--verbose`, `-v`
+  /// or `-vv...` out of `args`, returning the rest, in order, for the day to
+  /// make sense of.
+  pub fn from_args(args: impl IntoIterator<Item = String>) -> Result<(Options, Vec<String>), Error> {
+    let mut options = Options::default();
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+      match arg.as_str() {
+        "--format" => {
+          options.format = match args.next().as_deref() {
+            Some("text") => Format::Text,
+            Some("json") => Format::Json,
+            other => return Err(anyhow!("Expected text or json after --format, got {:?}", other)),
+          }
+        }
+        "--batch" => options.batch = Some(args.next().ok_or_else(|| anyhow!("Missing path after --batch"))?),
+        "--verbose" => options.verbosity = options.verbosity.saturating_add(1),
+        v if v.len() > 1 && v.strip_prefix('-').is_some_and(|v| v.bytes().all(|b| b == b'v')) => {
+          let count = u8::try_from(v.len() - 1).unwrap_or(u8::MAX);
+          options.verbosity = options.verbosity.saturating_add(count)
+        }
+        _ => rest.push(arg),
+      }
+    }
+    Ok((options, rest))
+  }
+
+  /// Prints the answers to stdout in the chosen format.
+  pub fn print(&self, answers: &Answers) {
+    match self.format {
+      Format::Text => print!("{}", answers),
+      Format::Json => println!("{}", answers.to_json()),
+    }
+  }
+
+  /// Solves the input on stdin and prints the answers, or with `--batch`,
+  /// solves every input it names and prints a table of them.
+  pub fn run(&self, solve: impl Fn(&str) -> Result<Answers, Error> + Sync) -> Result<(), Error> {
+    if let Some(pattern) = &self.batch {
+      return batch::run_and_print(pattern, self.format, solve);
+    }
+    let mut input = String::new();
+    io::stdin().read_to_string(&mut input)?;
+    self.print(&solve(&input)?);
+    Ok(())
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn formats() {
+    let answers = Answers::new(1602, "0123");
+    assert_eq!(answers.to_string(), "part 1: 1602\npart 2: 0123\n");
+    assert_eq!(answers.to_json(), "{\"part1\": 1602, \"part2\": \"0123\"}");
+
+    let big = "1020847100762815390408570566369014185982";
+    let answers = Answers {
+      part1: Some(big.to_owned()),
+      part2: None,
+    };
+    assert_eq!(answers.to_string(), format!("part 1: {}\n", big));
+    assert_eq!(answers.to_json(), format!("{{\"part1\": {}, \"part2\": null}}", big));
+    assert_eq!(Answers::new(-3, "a\"b").to_json(), "{\"part1\": -3, \"part2\": \"a\\\"b\"}");
+
+    let answers = Answers::new(1602, "0123");
+    assert_eq!(answers.to_string().parse::<Answers>().unwrap(), answers);
+    assert_eq!("\npart 2: 7 \n".parse::<Answers>().unwrap().part2.as_deref(), Some("7"));
+    assert!("part 3: 1".parse::<Answers>().is_err());
+    assert!("7".parse::<Answers>().is_err());
+  }
+
+  #[test]
+  fn args() {
+    let args = |s: &str| s.split_whitespace().map(str::to_owned).collect::<Vec<_>>();
+
+    let (options, rest) = Options::from_args(args("analyse --format json --width 3 -v")).unwrap();
+    assert_eq!(
+      options,
+      Options {
+        format: Format::Json,
+        verbosity: 1,
+        batch: None,
+      }
+    );
+    assert_eq!(rest, args("analyse --width 3"));
+
+    let (options, rest) = Options::from_args(args("-vv - --verbose -x")).unwrap();
+    assert_eq!(options.verbosity, 3);
+    assert_eq!(rest, args("- -x"));
+
+    // Far more than anyone would type, but it shouldn't overflow.
+    let many = format!("-{} -vv --verbose", "v".repeat(300));
+    assert_eq!(Options::from_args(args(&many)).unwrap().0.verbosity, u8::MAX);
+
+    let (options, _) = Options::from_args(args("--batch inputs/*.txt")).unwrap();
+    assert_eq!(options.batch.as_deref(), Some("inputs/*.txt"));
+    assert!(Options::from_args(args("--batch")).is_err());
+
+    assert!(Options::from_args(args("--format yaml")).is_err());
+    assert!(Options::from_args(args("--format")).is_err());
+  }
+}
@@ -0,0 +1,326 @@
+//! Running a day over a whole directory of inputs at once, e.g. everyone's
+//! inputs and the edge cases we've collected, rather than one on stdin.
+//!
+//! An input can have a sidecar file next to it, named after it with
+//! `.answers` on the end, holding what the answers should be in the same
+//! form a day prints them (`part 1: ...`), so the output of a run can be
+//! saved as the expected answers.
+
+use {
+  crate::answers::{Answers, Format},
+  anyhow::{anyhow, Context, Error},
+  rayon::prelude::*,
+  std::{
+    fmt::Write as _,
+    any::Any,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
+  },
+  tracing::info_span,
+};
+
+/// What sidecar files end with.
+pub const SIDECAR: &str = "answers";
+
+/// The inputs `pattern` names: every file in it if it's a directory,
+/// otherwise every file matching it as a glob, in order. Sidecars are left
+/// out.
+pub fn inputs(pattern: &str) -> Result<Vec<PathBuf>, Error> {
+  let mut paths = if Path::new(pattern).is_dir() {
+    fs::read_dir(pattern)?
+      .map(|entry| Ok(entry?.path()))
+      .collect::<Result<Vec<_>, Error>>()?
+  } else {
+    glob::glob(pattern)?.collect::<Result<Vec<_>, _>>()?
+  };
+  paths.retain(|path| path.is_file() && path.extension().is_none_or(|ext| ext != SIDECAR));
+  paths.sort();
+  if paths.is_empty() {
+    return Err(anyhow!("No input files found in {}", pattern));
+  }
+  Ok(paths)
+}
+
+/// The sidecar holding `input`'s expected answers.
+pub fn sidecar(input: &Path) -> PathBuf {
+  let mut name = input.as_os_str().to_owned();
+  name.push(".");
+  name.push(SIDECAR);
+  PathBuf::from(name)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+  /// Solved, with nothing to check the answers against.
+  Solved,
+  /// Solved, and the answers match the sidecar.
+  Correct,
+  /// Solved, but not every answer matches the sidecar.
+  Wrong(String),
+  /// The input couldn't be read or solved.
+  Failed(String),
+}
+
+impl Status {
+  pub fn is_ok(&self) -> bool {
+    matches!(self, Status::Solved | Status::Correct)
+  }
+}
+
+/// How solving one input went.
+#[derive(Debug)]
+pub struct Outcome {
+  pub file: PathBuf,
+  /// Missing if solving failed.
+  pub answers: Option<Answers>,
+  pub time: Duration,
+  pub status: Status,
+}
+
+/// Compares `answers` to `expected`, only checking the parts `expected` has.
+fn check(answers: &Answers, expected: &Answers) -> Status {
+  let wrong = [(1, &answers.part1, &expected.part1), (2, &answers.part2, &expected.part2)]
+    .into_iter()
+    .filter_map(|(part, actual, expected)| match (actual, expected) {
+      (_, None) => None,
+      (Some(actual), Some(expected)) if actual == expected => None,
+      (actual, Some(expected)) => Some(format!(
+        "part {} is {}, expected {}",
+        part,
+        actual.as_deref().unwrap_or("missing"),
+        expected
+      )),
+    })
+    .collect::<Vec<_>>();
+  if wrong.is_empty() {
+    Status::Correct
+  } else {
+    Status::Wrong(wrong.join("; "))
+  }
+}
+
+/// What a panic said, if it said anything.
+fn panic_message(payload: &(dyn Any + Send)) -> &str {
+  payload
+    .downcast_ref::<&str>()
+    .copied()
+    .or_else(|| payload.downcast_ref::<String>().map(String::as_str))
+    .unwrap_or("no message")
+}
+
+/// Solves one input, timing only `solve` itself. A panic only fails this
+/// input, so one odd file doesn't cost the results for the rest.
+fn solve_file<F>(file: &Path, solve: &F) -> Outcome
+where
+  F: Fn(&str) -> Result<Answers, Error>,
+{
+  let _span = info_span!("file", path = %file.display()).entered();
+  let failed = |message: String| Outcome {
+    file: file.to_owned(),
+    answers: None,
+    time: Duration::ZERO,
+    status: Status::Failed(message),
+  };
+
+  let input = match fs::read_to_string(file) {
+    Ok(input) => input,
+    Err(e) => return failed(format!("couldn't read input: {}", e)),
+  };
+  let start = Instant::now();
+  let answers = panic::catch_unwind(AssertUnwindSafe(|| solve(&input)));
+  let time = start.elapsed();
+  let answers = match answers {
+    Ok(Ok(answers)) => answers,
+    Ok(Err(e)) => return Outcome { time, ..failed(format!("{:#}", e)) },
+    Err(payload) => return Outcome { time, ..failed(format!("panicked: {}", panic_message(&*payload))) },
+  };
+
+  let sidecar = sidecar(file);
+  let status = if !sidecar.exists() {
+    Status::Solved
+  } else {
+    let expected = fs::read_to_string(&sidecar)
+      .map_err(Error::from)
+      .and_then(|s| s.parse::<Answers>())
+      .with_context(|| format!("couldn't read {}", sidecar.display()));
+    match expected {
+      Ok(expected) => check(&answers, &expected),
+      Err(e) => Status::Failed(format!("{:#}", e)),
+    }
+  };
+  Outcome {
+    file: file.to_owned(),
+    answers: Some(answers),
+    time,
+    status,
+  }
+}
+
+/// Solves every file, in parallel, returning how each went in the same
+/// order.
+pub fn run<F>(files: &[PathBuf], solve: F) -> Vec<Outcome>
+where
+  F: Fn(&str) -> Result<Answers, Error> + Sync,
+{
+  files.par_iter().map(|file| solve_file(file, &solve)).collect()
+}
+
+/// A table with a row per outcome, lined up in columns.
+pub fn table(outcomes: &[Outcome]) -> String {
+  let rows = outcomes
+    .iter()
+    .map(|outcome| {
+      let part = |part: fn(&Answers) -> &Option<String>| {
+        outcome.answers.as_ref().and_then(|a| part(a).as_deref()).unwrap_or("-").to_owned()
+      };
+      let status = match &outcome.status {
+        Status::Solved => "ok".to_owned(),
+        Status::Correct => "correct".to_owned(),
+        Status::Wrong(message) => format!("WRONG: {}", message),
+        Status::Failed(message) => format!("ERROR: {}", message),
+      };
+      [
+        outcome.file.display().to_string(),
+        part(|a| &a.part1),
+        part(|a| &a.part2),
+        format!("{:.1?}", outcome.time),
+        status,
+      ]
+    })
+    .collect::<Vec<_>>();
+
+  let header = ["file", "part 1", "part 2", "time", "result"].map(str::to_owned);
+  let mut widths = header.clone().map(|h| h.chars().count());
+  for row in &rows {
+    for (width, cell) in widths.iter_mut().zip(row) {
+      *width = (*width).max(cell.chars().count());
+    }
+  }
+
+  let mut out = String::new();
+  for row in std::iter::once(&header).chain(&rows) {
+    let mut line = String::new();
+    for (i, (cell, width)) in row.iter().zip(widths).enumerate() {
+      match i {
+        // The answers and timings are right-aligned, so they line up.
+        1..=3 => write!(line, "{:>w$}  ", cell, w = width),
+        _ => write!(line, "{:<w$}  ", cell, w = width),
+      }
+      .unwrap();
+    }
+    writeln!(out, "{}", line.trim_end()).unwrap();
+  }
+  out
+}
+
+/// One JSON object per outcome, a line each.
+pub fn json_lines(outcomes: &[Outcome]) -> String {
+  let mut out = String::new();
+  for outcome in outcomes {
+    let (status, message) = match &outcome.status {
+      Status::Solved => ("solved", None),
+      Status::Correct => ("correct", None),
+      Status::Wrong(message) => ("wrong", Some(message)),
+      Status::Failed(message) => ("failed", Some(message)),
+    };
+    writeln!(
+      out,
+      "{{\"file\": {}, \"answers\": {}, \"seconds\": {}, \"status\": \"{}\", \"message\": {}}}",
+      serde_json::to_string(&outcome.file.display().to_string()).unwrap(),
+      outcome.answers.as_ref().map_or("null".to_owned(), Answers::to_json),
+      outcome.time.as_secs_f64(),
+      status,
+      serde_json::to_string(&message).unwrap()
+    )
+    .unwrap();
+  }
+  out
+}
+
+/// Runs `solve` over everything `pattern` names and prints the outcomes,
+/// failing if any input failed or got the wrong answers.
+pub fn run_and_print<F>(pattern: &str, format: Format, solve: F) -> Result<(), Error>
+where
+  F: Fn(&str) -> Result<Answers, Error> + Sync,
+{
+  let outcomes = run(&inputs(pattern)?, solve);
+  match format {
+    Format::Text => print!("{}", table(&outcomes)),
+    Format::Json => print!("{}", json_lines(&outcomes)),
+  }
+  let failed = outcomes.iter().filter(|outcome| !outcome.status.is_ok()).count();
+  if failed > 0 {
+    return Err(anyhow!("{} of {} inputs failed", failed, outcomes.len()));
+  }
+  Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn batch() {
+    let dir = std::env::temp_dir().join(format!("util-batch-{}", std::process::id()));
+    fs::create_dir_all(&dir).unwrap();
+    for (name, contents) in [
+      ("a.txt", "1\n2\n"),
+      ("a.txt.answers", "part 1: 3\npart 2: 2\n"),
+      ("b.txt", "5\n"),
+      ("b.txt.answers", "part 1: 6\n"),
+      ("c.txt", "4\nx\n"),
+      ("d.txt", "7\n"),
+    ] {
+      fs::write(dir.join(name), contents).unwrap();
+    }
+
+    // Sums the lines, and counts them.
+    let solve = |s: &str| -> Result<Answers, Error> {
+      let values = crate::parse::values::<u32>(s)?;
+      Ok(Answers::new(values.iter().sum::<u32>(), values.len()))
+    };
+    let files = inputs(dir.to_str().unwrap()).unwrap();
+    assert_eq!(files.len(), 4);
+    let outcomes = run(&files, solve);
+    let statuses = outcomes.iter().map(|o| o.status.clone()).collect::<Vec<_>>();
+    assert_eq!(
+      statuses,
+      vec![
+        Status::Correct,
+        Status::Wrong("part 1 is 5, expected 6".to_owned()),
+        Status::Failed("line 2, column 1: couldn't parse \"x\": invalid digit found in string".to_owned()),
+        Status::Solved,
+      ]
+    );
+
+    let table = table(&outcomes);
+    assert!(table.starts_with("file"));
+    assert!(table.contains("WRONG: part 1 is 5, expected 6"));
+    assert_eq!(table.lines().count(), 5);
+    let json = json_lines(&outcomes);
+    assert!(json.lines().next().unwrap().contains("\"answers\": {\"part1\": 3, \"part2\": 2}"));
+    assert!(json.contains("\"answers\": null"));
+
+    // A panic only fails the input that caused it.
+    let outcomes = run(&files, |s: &str| -> Result<Answers, Error> {
+      match s {
+        "5\n" => panic!("can't handle {:?}", s),
+        "7\n" => std::panic::panic_any(7),
+        _ => solve(s),
+      }
+    });
+    let statuses = outcomes.iter().map(|o| o.status.clone()).collect::<Vec<_>>();
+    assert_eq!(statuses[0], Status::Correct);
+    assert_eq!(statuses[1], Status::Failed("panicked: can't handle \"5\\n\"".to_owned()));
+    assert_eq!(statuses[3], Status::Failed("panicked: no message".to_owned()));
+
+    // Globs work too.
+    let pattern = dir.join("[ab].txt");
+    assert_eq!(inputs(pattern.to_str().unwrap()).unwrap().len(), 2);
+    assert!(inputs(dir.join("*.nothing").to_str().unwrap()).is_err());
+
+    fs::remove_dir_all(&dir).unwrap();
+  }
+}
@@ -0,0 +1,54 @@
+//! `gen DAY [--size N] [--seed S] [--answers FILE]` writes a generated input
+//! for DAY to stdout, and its answers to FILE, or to stderr without one.
+//! `gen list` shows what each day's size counts.
+
+use {
+  anyhow::{anyhow, Error},
+  std::{env, fs},
+  util::generators::{self, Options},
+};
+
+fn main() -> Result<(), Error> {
+  let mut args = env::args().skip(1);
+  let day = args
+    .next()
+    .ok_or_else(|| anyhow!("Usage: gen DAY [--size N] [--seed S] [--answers FILE]"))?;
+
+  if day == "list" {
+    for day in &generators::DAYS {
+      println!("{:>2} {:<22} size = {}", day.day, day.name, day.size);
+    }
+    return Ok(());
+  }
+
+  let day = day
+    .parse()
+    .ok()
+    .and_then(generators::day)
+    .ok_or_else(|| anyhow!("No generator for day {}", day))?;
+
+  let mut options = Options::default();
+  let mut answers = None;
+  while let Some(arg) = args.next() {
+    let value = args
+      .next()
+      .ok_or_else(|| anyhow!("Missing value for {}", arg))?;
+    match arg.as_str() {
+      "--size" => options.size = value.parse().map_err(|_| anyhow!("Invalid size: {}", value))?,
+      "--seed" => options.seed = value.parse().map_err(|_| anyhow!("Invalid seed: {}", value))?,
+      "--answers" => answers = Some(value),
+      other => return Err(anyhow!("Unknown argument: {}", other)),
+    }
+  }
+
+  let generated = day.generate(&options);
+  print!("{}", generated.input);
+  let [part1, part2] = generated.answers;
+  let summary = format!("part 1: {}\npart 2: {}\n", part1, part2);
+  match answers {
+    Some(path) => fs::write(path, summary)?,
+    None => eprint!("{}", summary),
+  }
+
+  Ok(())
+}
@@ -0,0 +1,167 @@
+//! Errors that point at the part of the input they're about.
+//!
+//! Parsers return a `Diagnostic` inside an `anyhow::Error`, which reads as
+//! `line L, column C: message`. A day's `main` returns `Result<(), Report>`
+//! so that if it fails on a diagnostic, the offending line is printed with a
+//! caret under the bad text.
+
+use {
+  anyhow::Error,
+  std::{fmt, ops::Range},
+};
+
+/// A problem with some part of an input file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+  /// The name of the file the input came from, if it wasn't stdin.
+  pub file: Option<String>,
+  /// Byte offsets into the input. Never extends past the end of the line it
+  /// starts on.
+  pub span: Range<usize>,
+  /// One based.
+  pub line: usize,
+  /// One based, in characters.
+  pub column: usize,
+  pub message: String,
+  /// The whole of the line the span is on, without its line ending.
+  pub source: String,
+}
+
+/// The line of `full` containing byte `offset`, and the offset of its start.
+fn line_at(full: &str, offset: usize) -> (&str, usize) {
+  let start = full[..offset].rfind('\n').map_or(0, |i| i + 1);
+  let line = full[start..].split('\n').next().unwrap_or_default();
+  (line.strip_suffix('\r').unwrap_or(line), start)
+}
+
+impl Diagnostic {
+  /// A diagnostic about `part`, a slice of `full`, or as much of it as is on
+  /// its first line.
+  pub fn spanning(full: &str, part: &str, message: impl fmt::Display) -> Diagnostic {
+    let offset = part.as_ptr() as usize - full.as_ptr() as usize;
+    assert!(offset <= full.len(), "not a slice of the input");
+    let (source, line_start) = line_at(full, offset);
+    let end = (offset + part.len()).min(line_start + source.len()).max(offset);
+    Diagnostic {
+      file: None,
+      span: offset..end,
+      line: full[..offset].matches('\n').count() + 1,
+      column: full[line_start..offset].chars().count() + 1,
+      message: message.to_string(),
+      source: source.to_owned(),
+    }
+  }
+
+  /// A diagnostic pointing at the character where `part`, a slice of `full`,
+  /// begins.
+  pub fn at(full: &str, part: &str, message: impl fmt::Display) -> Diagnostic {
+    let len = part.chars().next().map_or(0, char::len_utf8);
+    Diagnostic::spanning(full, &part[..len], message)
+  }
+
+  /// Shows the line the diagnostic is on, with the span underlined.
+  pub fn render(&self) -> String {
+    let location = match &self.file {
+      Some(file) => format!("{}:{}:{}", file, self.line, self.column),
+      None => format!("line {}, column {}", self.line, self.column),
+    };
+    let gutter = " ".repeat(self.line.to_string().len());
+    // Keep any tabs before the span so the carets line up under it.
+    let indent = self
+      .source
+      .chars()
+      .take(self.column - 1)
+      .map(|c| if c == '\t' { '\t' } else { ' ' })
+      .collect::<String>();
+    let start = self.source.char_indices().nth(self.column - 1).map_or(self.source.len(), |(i, _)| i);
+    let width = self.source[start..start + self.span.len()].chars().count().max(1);
+    format!(
+      "{}\n{}--> {}\n{} |\n{} | {}\n{} | {}{}\n",
+      self.message,
+      gutter,
+      location,
+      gutter,
+      self.line,
+      self.source,
+      gutter,
+      indent,
+      "^".repeat(width)
+    )
+  }
+}
+
+impl fmt::Display for Diagnostic {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    if let Some(file) = &self.file {
+      write!(f, "{}, ", file)?;
+    }
+    write!(f, "line {}, column {}: {}", self.line, self.column, self.message)
+  }
+}
+
+impl std::error::Error for Diagnostic {}
+
+/// Names the file a diagnostic came from, if `err` is one.
+pub fn in_file(mut err: Error, file: &str) -> Error {
+  if let Some(diagnostic) = err.downcast_mut::<Diagnostic>() {
+    diagnostic.file = Some(file.to_owned());
+  }
+  err
+}
+
+/// What a day's `main` returns. It converts from any error `?` would, and is
+/// printed with a snippet of the input if it's a diagnostic.
+pub struct Report(pub Error);
+
+impl<E: Into<Error>> From<E> for Report {
+  fn from(err: E) -> Report {
+    Report(err.into())
+  }
+}
+
+impl fmt::Debug for Report {
+  fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+    match self.0.downcast_ref::<Diagnostic>() {
+      Some(diagnostic) => f.write_str(diagnostic.render().trim_end()),
+      None => fmt::Debug::fmt(&self.0, f),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn spans() {
+    let full = "12\n3x4\r\n5";
+    let d = Diagnostic::at(full, &full[4..], "expected a digit");
+    assert_eq!((d.line, d.column, d.span.clone()), (2, 2, 4..5));
+    assert_eq!(d.source, "3x4");
+    assert_eq!(d.to_string(), "line 2, column 2: expected a digit");
+
+    // Clamped to the line, without the line ending.
+    let d = Diagnostic::spanning(full, &full[3..], "bad line");
+    assert_eq!(d.span, 3..6);
+
+    // At the very end of the input.
+    let d = Diagnostic::at(full, &full[full.len()..], "expected end of line");
+    assert_eq!((d.line, d.column, d.span.clone()), (3, 2, 9..9));
+  }
+
+  #[test]
+  fn render() {
+    let full = "1,2 -> 3,4\n\t5,6 -> 7;8\n";
+    let d = Diagnostic::spanning(full, &full[19..21], "expected \",\"");
+    assert_eq!(
+      d.render(),
+      "expected \",\"\n --> line 2, column 9\n  |\n2 | \t5,6 -> 7;8\n  | \t       ^^\n"
+    );
+
+    let err = in_file(Error::new(d), "input.txt");
+    assert_eq!(err.to_string(), "input.txt, line 2, column 9: expected \",\"");
+    assert!(format!("{:?}", Report::from(err)).contains(" --> input.txt:2:9\n"));
+
+    assert_eq!(format!("{:?}", Report::from(anyhow::anyhow!("no input"))), "no input");
+  }
+}
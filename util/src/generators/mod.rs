@@ -0,0 +1,181 @@
+//! Seeded generators for every day's puzzle input, for checking how the
+//! solvers scale beyond the one `input.txt` we have.
+//!
+//! The same day, size and seed always give the same input. Each generator
+//! also works out both answers, either by construction or with a simple
+//! reference solution that doesn't share any code with the solver.
+
+mod p01;
+mod p02;
+mod p03;
+mod p04;
+mod p05;
+mod p06;
+mod p07;
+mod p08;
+mod p09;
+
+use {
+  crate::answers::Answers,
+  anyhow::Error,
+};
+
+/// SplitMix64: small, fast, and stable across platforms and releases, which
+/// is all a reproducible input needs.
+pub struct Rng {
+  state: u64,
+}
+
+impl Rng {
+  pub fn new(seed: u64) -> Rng {
+    Rng { state: seed }
+  }
+
+  pub fn next_u64(&mut self) -> u64 {
+    self.state = self.state.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    let mut z = self.state;
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+  }
+
+  /// A number in `0..n`. The modulo bias is negligible for the ranges used
+  /// here.
+  pub fn below(&mut self, n: u64) -> u64 {
+    assert!(n > 0);
+    self.next_u64() % n
+  }
+
+  /// A number in `lo..=hi`.
+  pub fn range(&mut self, lo: i64, hi: i64) -> i64 {
+    assert!(lo <= hi);
+    lo + self.below((hi - lo) as u64 + 1) as i64
+  }
+
+  /// True with probability `p`.
+  pub fn chance(&mut self, p: f64) -> bool {
+    ((self.next_u64() >> 11) as f64 / (1u64 << 53) as f64) < p
+  }
+
+  /// Fisher-Yates.
+  pub fn shuffle<T>(&mut self, items: &mut [T]) {
+    for i in (1..items.len()).rev() {
+      items.swap(i, self.below(i as u64 + 1) as usize);
+    }
+  }
+}
+
+/// A generated input along with its answers.
+pub struct Generated {
+  pub input: String,
+  pub answers: [u64; 2],
+}
+
+/// What `size` means differs by day; see `DAYS`.
+pub struct Options {
+  pub seed: u64,
+  pub size: usize,
+}
+
+impl Default for Options {
+  fn default() -> Options {
+    Options { seed: 0, size: 1000 }
+  }
+}
+
+pub struct Day {
+  pub day: u32,
+  pub name: &'static str,
+  /// What `Options::size` counts.
+  pub size: &'static str,
+  generate: fn(&mut Rng, usize) -> Generated,
+}
+
+impl Day {
+  pub fn generate(&self, options: &Options) -> Generated {
+    (self.generate)(&mut Rng::new(options.seed), options.size)
+  }
+}
+
+pub const DAYS: [Day; 9] = [
+  Day { day: 1, name: "sonar-sweep", size: "depths", generate: p01::generate },
+  Day { day: 2, name: "dive", size: "commands", generate: p02::generate },
+  Day { day: 3, name: "binary-diagnostic", size: "report lines", generate: p03::generate },
+  Day { day: 4, name: "giant-squid", size: "boards", generate: p04::generate },
+  Day { day: 5, name: "hydrothermal-venture", size: "vent lines", generate: p05::generate },
+  Day { day: 6, name: "lanternfish", size: "fish", generate: p06::generate },
+  Day { day: 7, name: "whale-treachery", size: "crabs", generate: p07::generate },
+  Day { day: 8, name: "seven-segment-search", size: "displays", generate: p08::generate },
+  Day { day: 9, name: "smoke-basin", size: "rows and columns", generate: p09::generate },
+];
+
+pub fn day(day: u32) -> Option<&'static Day> {
+  DAYS.iter().find(|d| d.day == day)
+}
+
+/// Generated inputs for `day` of the given size, with their answers, one for
+/// each seed.
+pub fn cases(day: u32, size: usize, seeds: impl IntoIterator<Item = u64>) -> impl Iterator<Item = (String, Answers)> {
+  let day = self::day(day).unwrap_or_else(|| panic!("No generator for day {}", day));
+  seeds.into_iter().map(move |seed| {
+    let Generated { input, answers: [part1, part2] } = day.generate(&Options { seed, size });
+    (input, Answers::new(part1, part2))
+  })
+}
+
+/// Checks `solve` against a few of `day`'s generated inputs, panicking at the
+/// first seed it gets wrong. The generators work out their answers without
+/// any of the solvers' code, so this catches mistakes the sample and our one
+/// `input.txt` don't happen to exercise.
+pub fn check<F>(day: u32, size: usize, solve: F)
+where
+  F: Fn(&str) -> Result<Answers, Error>,
+{
+  for (seed, (input, expected)) in cases(day, size, 0..3).enumerate() {
+    let answers = solve(&input).unwrap_or_else(|e| panic!("day {}, seed {}: {:#}", day, seed, e));
+    assert_eq!(answers, expected, "day {}, seed {}", day, seed);
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn deterministic() {
+    for day in &DAYS {
+      let options = Options { seed: 7, size: 50 };
+      let (a, b) = (day.generate(&options), day.generate(&options));
+      assert_eq!((&a.input, a.answers), (&b.input, b.answers), "day {}", day.day);
+
+      let other = day.generate(&Options { seed: 8, size: 50 });
+      assert_ne!(a.input, other.input, "day {}", day.day);
+      assert!(a.input.ends_with('\n'), "day {}", day.day);
+    }
+  }
+
+  #[test]
+  fn cases_and_check() {
+    let (input, answers) = cases(6, 5, [7]).next().unwrap();
+    let generated = day(6).unwrap().generate(&Options { seed: 7, size: 5 });
+    assert_eq!((input, answers), (generated.input, Answers::new(generated.answers[0], generated.answers[1])));
+
+    // Day 7's answers are its sums of distances, which a solver that ignores
+    // its input can't get right.
+    let result = std::panic::catch_unwind(|| check(7, 5, |_| Ok(Answers::new(0, 0))));
+    assert!(result.is_err());
+  }
+
+  #[test]
+  fn rng() {
+    let mut rng = Rng::new(1);
+    let mut items = (0..100).collect::<Vec<_>>();
+    rng.shuffle(&mut items);
+    assert_ne!(items, (0..100).collect::<Vec<_>>());
+    items.sort_unstable();
+    assert_eq!(items, (0..100).collect::<Vec<_>>());
+
+    assert!((0..1000).map(|_| rng.range(-3, 3)).all(|n| (-3..=3).contains(&n)));
+    assert!((0..1000).all(|_| !rng.chance(0.0)));
+  }
+}
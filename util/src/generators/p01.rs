@@ -0,0 +1,40 @@
+//! A sonar sweep: a mostly deepening random walk.
+
+use {
+  super::{Generated, Rng},
+  std::fmt::Write,
+};
+
+fn increases(depths: &[u64], k: usize) -> u64 {
+  let sums = depths.windows(k).map(|w| w.iter().sum::<u64>()).collect::<Vec<_>>();
+  sums.windows(2).filter(|w| w[1] > w[0]).count() as u64
+}
+
+pub(super) fn generate(rng: &mut Rng, size: usize) -> Generated {
+  let mut depth = rng.range(100, 200) as u64;
+  let mut depths = Vec::with_capacity(size);
+  for _ in 0..size {
+    depths.push(depth);
+    depth = depth.saturating_add_signed(rng.range(-10, 20));
+  }
+
+  let mut input = String::new();
+  for depth in &depths {
+    writeln!(input, "{}", depth).unwrap();
+  }
+  Generated {
+    input,
+    answers: [increases(&depths, 1), increases(&depths, 3)],
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sample() {
+    let depths = [199, 200, 208, 210, 200, 207, 240, 269, 260, 263];
+    assert_eq!((increases(&depths, 1), increases(&depths, 3)), (7, 5));
+  }
+}
@@ -0,0 +1,68 @@
+//! A list of submarine commands that never tries to fly.
+
+use {
+  super::{Generated, Rng},
+  std::fmt::Write,
+};
+
+/// Part 1 and part 2's final horizontal position times depth.
+fn answers(commands: &[(&str, i64)]) -> [u64; 2] {
+  let (mut x, mut depth, mut aim, mut aimed_depth) = (0i64, 0i64, 0i64, 0i64);
+  for (command, n) in commands {
+    match *command {
+      "forward" => {
+        x += n;
+        aimed_depth += aim * n;
+      }
+      "down" => {
+        depth += n;
+        aim += n;
+      }
+      "up" => {
+        depth -= n;
+        aim -= n;
+      }
+      _ => unreachable!(),
+    }
+  }
+  [(x * depth) as u64, (x * aimed_depth) as u64]
+}
+
+pub(super) fn generate(rng: &mut Rng, size: usize) -> Generated {
+  let mut depth = 0;
+  let mut commands = Vec::with_capacity(size);
+  for _ in 0..size {
+    let n = rng.range(1, 9);
+    let command = match rng.below(3) {
+      0 => "forward",
+      1 if depth >= n => "up",
+      _ => "down",
+    };
+    match command {
+      "up" => depth -= n,
+      "down" => depth += n,
+      _ => {}
+    }
+    commands.push((command, n));
+  }
+
+  let mut input = String::new();
+  for (command, n) in &commands {
+    writeln!(input, "{} {}", command, n).unwrap();
+  }
+  Generated {
+    answers: answers(&commands),
+    input,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sample() {
+    let commands = [("forward", 5), ("down", 5), ("forward", 8), ("up", 3), ("down", 8), ("forward", 2)];
+    assert_eq!(answers(&commands), [150, 900]);
+  }
+}
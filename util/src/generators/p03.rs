@@ -0,0 +1,80 @@
+//! A diagnostic report of distinct binary numbers.
+
+use {
+  super::{Generated, Rng},
+  std::{collections::BTreeSet, fmt::Write},
+};
+
+/// Whether bit `bit` (counting from the most significant of `width`) is more
+/// often set than not in `values`, with ties going to set.
+fn mostly_set(values: &[u64], width: usize, bit: usize) -> bool {
+  let ones = values.iter().filter(|v| *v >> (width - 1 - bit) & 1 == 1).count();
+  ones * 2 >= values.len()
+}
+
+/// Keeps the values agreeing with `keep_set(mostly_set)` at each bit in turn
+/// until one is left. If none agree, which happens when they all share a bit,
+/// they're all kept.
+fn rating(values: &[u64], width: usize, keep_set: impl Fn(bool) -> bool) -> u64 {
+  let mut values = values.to_vec();
+  for bit in 0..width {
+    if values.len() == 1 {
+      break;
+    }
+    let set = keep_set(mostly_set(&values, width, bit));
+    let kept = values
+      .iter()
+      .copied()
+      .filter(|v| (*v >> (width - 1 - bit) & 1 == 1) == set)
+      .collect::<Vec<_>>();
+    if !kept.is_empty() {
+      values = kept;
+    }
+  }
+  values[0]
+}
+
+fn answers(values: &[u64], width: usize) -> [u64; 2] {
+  let gamma = (0..width).fold(0, |gamma, bit| gamma << 1 | mostly_set(values, width, bit) as u64);
+  let epsilon = !gamma & ((1 << width) - 1);
+  let oxygen = rating(values, width, |mostly| mostly);
+  let co2 = rating(values, width, |mostly| !mostly);
+  [gamma * epsilon, oxygen * co2]
+}
+
+pub(super) fn generate(rng: &mut Rng, size: usize) -> Generated {
+  // Wide enough that there are plenty of distinct values to choose from;
+  // duplicates would stop the ratings narrowing down to one value.
+  let width = (usize::BITS - size.leading_zeros() + 1).max(12) as usize;
+  let mut seen = BTreeSet::new();
+  let mut values = Vec::with_capacity(size);
+  while values.len() < size.max(1) {
+    let value = rng.below(1 << width);
+    if seen.insert(value) {
+      values.push(value);
+    }
+  }
+
+  let mut input = String::new();
+  for value in &values {
+    writeln!(input, "{:0w$b}", value, w = width).unwrap();
+  }
+  Generated {
+    answers: answers(&values, width),
+    input,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sample() {
+    let values = [
+      0b00100, 0b11110, 0b10110, 0b10111, 0b10101, 0b01111, 0b00111, 0b11100, 0b10000, 0b11001, 0b00010,
+      0b01010,
+    ];
+    assert_eq!(answers(&values, 5), [198, 230]);
+  }
+}
@@ -0,0 +1,85 @@
+//! A bingo game in which every board eventually wins.
+
+use {
+  super::{Generated, Rng},
+  std::fmt::Write,
+};
+
+const SIDE: usize = 5;
+
+/// The first and last winning boards' scores, playing the slow way.
+fn answers(draws: &[u32], boards: &[Vec<u32>]) -> [u64; 2] {
+  let mut marked = vec![vec![false; SIDE * SIDE]; boards.len()];
+  let mut won = vec![false; boards.len()];
+  let mut scores = Vec::new();
+  for n in draws {
+    for (b, board) in boards.iter().enumerate() {
+      if won[b] {
+        continue;
+      }
+      if let Some(i) = board.iter().position(|m| m == n) {
+        marked[b][i] = true;
+      }
+      let row = (0..SIDE).any(|r| (0..SIDE).all(|c| marked[b][r * SIDE + c]));
+      let col = (0..SIDE).any(|c| (0..SIDE).all(|r| marked[b][r * SIDE + c]));
+      if row || col {
+        won[b] = true;
+        let unmarked = (0..SIDE * SIDE)
+          .filter(|i| !marked[b][*i])
+          .map(|i| board[i] as u64)
+          .sum::<u64>();
+        scores.push(unmarked * *n as u64);
+      }
+    }
+  }
+  [scores[0], *scores.last().unwrap()]
+}
+
+pub(super) fn generate(rng: &mut Rng, size: usize) -> Generated {
+  // Every number gets drawn, so every board wins.
+  let pool = (SIDE * SIDE * 4).max(size / 10) as u32;
+  let mut draws = (0..pool).collect::<Vec<_>>();
+  rng.shuffle(&mut draws);
+
+  let boards = (0..size.max(1))
+    .map(|_| {
+      let mut nums = (0..pool).collect::<Vec<_>>();
+      rng.shuffle(&mut nums);
+      nums.truncate(SIDE * SIDE);
+      nums
+    })
+    .collect::<Vec<_>>();
+
+  let mut input = draws.iter().map(|n| n.to_string()).collect::<Vec<_>>().join(",");
+  input.push('\n');
+  for board in &boards {
+    input.push('\n');
+    for row in board.chunks(SIDE) {
+      let row = row.iter().map(|n| format!("{:>2}", n)).collect::<Vec<_>>();
+      writeln!(input, "{}", row.join(" ")).unwrap();
+    }
+  }
+  Generated {
+    answers: answers(&draws, &boards),
+    input,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sample() {
+    let draws = [
+      7, 4, 9, 5, 11, 17, 23, 2, 0, 14, 21, 24, 10, 16, 13, 6, 15, 25, 12, 22, 18, 20, 8, 19, 3, 26, 1,
+    ];
+    let boards = [
+      [22, 13, 17, 11, 0, 8, 2, 23, 4, 24, 21, 9, 14, 16, 7, 6, 10, 3, 18, 5, 1, 12, 20, 15, 19],
+      [3, 15, 0, 2, 22, 9, 18, 13, 17, 5, 19, 8, 7, 25, 23, 20, 11, 10, 24, 4, 14, 21, 16, 12, 6],
+      [14, 21, 17, 24, 4, 10, 16, 15, 9, 19, 18, 8, 23, 26, 20, 22, 11, 13, 6, 5, 2, 0, 12, 3, 7],
+    ]
+    .map(Vec::from);
+    assert_eq!(answers(&draws, &boards), [4512, 1924]);
+  }
+}
@@ -0,0 +1,84 @@
+//! Vent lines: horizontal, vertical and at 45 degrees.
+
+use {
+  super::{Generated, Rng},
+  std::{collections::HashMap, fmt::Write},
+};
+
+type Line = ((i64, i64), (i64, i64));
+
+/// How many points at least two lines cross, without and with diagonals,
+/// by drawing every line.
+fn answers(lines: &[Line]) -> [u64; 2] {
+  let mut straight = HashMap::<(i64, i64), u32>::new();
+  let mut all = HashMap::<(i64, i64), u32>::new();
+  for ((x1, y1), (x2, y2)) in lines {
+    let (dx, dy) = ((x2 - x1).signum(), (y2 - y1).signum());
+    let len = (x2 - x1).abs().max((y2 - y1).abs());
+    for i in 0..=len {
+      let point = (x1 + i * dx, y1 + i * dy);
+      *all.entry(point).or_default() += 1;
+      if dx == 0 || dy == 0 {
+        *straight.entry(point).or_default() += 1;
+      }
+    }
+  }
+  let overlaps = |counts: &HashMap<_, u32>| counts.values().filter(|n| **n > 1).count() as u64;
+  [overlaps(&straight), overlaps(&all)]
+}
+
+pub(super) fn generate(rng: &mut Rng, size: usize) -> Generated {
+  const MAX: i64 = 999;
+  // Real inputs never have lines that are a single point, so neither do
+  // these.
+  let mut lines = Vec::with_capacity(size);
+  while lines.len() < size {
+    let start = (rng.range(0, MAX), rng.range(0, MAX));
+    let end = match rng.below(3) {
+      0 => (rng.range(0, MAX), start.1),
+      1 => (start.0, rng.range(0, MAX)),
+      _ => {
+        let (dx, dy) = (if rng.chance(0.5) { 1 } else { -1 }, if rng.chance(0.5) { 1 } else { -1 });
+        // No further than the grid allows in that direction.
+        let room_x = if dx > 0 { MAX - start.0 } else { start.0 };
+        let room_y = if dy > 0 { MAX - start.1 } else { start.1 };
+        let len = rng.range(0, room_x.min(room_y));
+        (start.0 + dx * len, start.1 + dy * len)
+      }
+    };
+    if start != end {
+      lines.push((start, end));
+    }
+  }
+
+  let mut input = String::new();
+  for ((x1, y1), (x2, y2)) in &lines {
+    writeln!(input, "{},{} -> {},{}", x1, y1, x2, y2).unwrap();
+  }
+  Generated {
+    answers: answers(&lines),
+    input,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sample() {
+    let lines = [
+      ((0, 9), (5, 9)),
+      ((8, 0), (0, 8)),
+      ((9, 4), (3, 4)),
+      ((2, 2), (2, 1)),
+      ((7, 0), (7, 4)),
+      ((6, 4), (2, 0)),
+      ((0, 9), (2, 9)),
+      ((3, 4), (1, 4)),
+      ((0, 0), (8, 8)),
+      ((5, 5), (8, 2)),
+    ];
+    assert_eq!(answers(&lines), [5, 12]);
+  }
+}
@@ -0,0 +1,38 @@
+//! A school of lanternfish timers.
+
+use super::{Generated, Rng};
+
+/// How many fish there are after `days`, tracking how many have each timer
+/// value rather than each fish.
+fn population(timers: &[u8], days: usize) -> u64 {
+  let mut counts = [0u64; 9];
+  for timer in timers {
+    counts[*timer as usize] += 1;
+  }
+  for _ in 0..days {
+    counts.rotate_left(1);
+    counts[6] += counts[8];
+  }
+  counts.iter().sum()
+}
+
+pub(super) fn generate(rng: &mut Rng, size: usize) -> Generated {
+  let timers = (0..size.max(1)).map(|_| rng.range(1, 5) as u8).collect::<Vec<_>>();
+  let timers_str = timers.iter().map(|t| t.to_string()).collect::<Vec<_>>();
+  Generated {
+    input: timers_str.join(",") + "\n",
+    answers: [population(&timers, 80), population(&timers, 256)],
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sample() {
+    let timers = [3, 4, 3, 1, 2];
+    assert_eq!(population(&timers, 18), 26);
+    assert_eq!([population(&timers, 80), population(&timers, 256)], [5934, 26984457539]);
+  }
+}
@@ -0,0 +1,47 @@
+//! Crab submarine positions.
+
+use super::{Generated, Rng};
+
+/// The least fuel to line every crab up, where moving `n` steps costs
+/// `cost(n)`. Both costs are convex, so the total is too, and the best
+/// position can be found by walking downhill.
+fn least_fuel(crabs: &[i64], cost: impl Fn(i64) -> i64) -> u64 {
+  let total = |target: i64| crabs.iter().map(|c| cost((c - target).abs())).sum::<i64>();
+  let (mut lo, mut hi) = (*crabs.iter().min().unwrap(), *crabs.iter().max().unwrap());
+  while hi - lo > 2 {
+    let (a, b) = (lo + (hi - lo) / 3, hi - (hi - lo) / 3);
+    if total(a) <= total(b) {
+      hi = b;
+    } else {
+      lo = a;
+    }
+  }
+  (lo..=hi).map(total).min().unwrap() as u64
+}
+
+fn answers(crabs: &[i64]) -> [u64; 2] {
+  [least_fuel(crabs, |n| n), least_fuel(crabs, |n| n * (n + 1) / 2)]
+}
+
+pub(super) fn generate(rng: &mut Rng, size: usize) -> Generated {
+  let spread = (size as i64 * 2).max(10);
+  // Bunched towards the low end, like the real input.
+  let crabs = (0..size.max(1))
+    .map(|_| rng.range(0, spread).min(rng.range(0, spread)))
+    .collect::<Vec<_>>();
+  let crabs_str = crabs.iter().map(|c| c.to_string()).collect::<Vec<_>>();
+  Generated {
+    input: crabs_str.join(",") + "\n",
+    answers: answers(&crabs),
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sample() {
+    assert_eq!(answers(&[16, 1, 2, 0, 4, 2, 7, 1, 2, 14]), [37, 168]);
+  }
+}
@@ -0,0 +1,95 @@
+//! Seven segment displays with scrambled wiring. The answers are known by
+//! construction: we pick the digits, then scramble them.
+
+use {
+  super::{Generated, Rng},
+  std::fmt::Write,
+};
+
+/// The segments lit for each digit.
+const DIGITS: [&str; 10] = ["abcefg", "cf", "acdeg", "acdfg", "bcdf", "abdfg", "abdefg", "acf", "abcdefg", "abcdfg"];
+
+/// `digit` as shown through `wiring`, which maps each segment to the wire
+/// driving it, with the wires in a random order.
+fn scramble(rng: &mut Rng, wiring: &[u8], digit: usize) -> String {
+  let mut wires = DIGITS[digit].bytes().map(|s| wiring[(s - b'a') as usize]).collect::<Vec<_>>();
+  rng.shuffle(&mut wires);
+  String::from_utf8(wires).unwrap()
+}
+
+pub(super) fn generate(rng: &mut Rng, size: usize) -> Generated {
+  let mut input = String::new();
+  let (mut easy, mut sum) = (0, 0);
+  for _ in 0..size {
+    let mut wiring = *b"abcdefg";
+    rng.shuffle(&mut wiring);
+
+    let mut patterns = (0..10).collect::<Vec<_>>();
+    rng.shuffle(&mut patterns);
+    let outputs = (0..4).map(|_| rng.below(10) as usize).collect::<Vec<_>>();
+
+    let patterns = patterns.iter().map(|d| scramble(rng, &wiring, *d)).collect::<Vec<_>>();
+    let shown = outputs.iter().map(|d| scramble(rng, &wiring, *d)).collect::<Vec<_>>();
+    writeln!(input, "{} | {}", patterns.join(" "), shown.join(" ")).unwrap();
+
+    easy += outputs.iter().filter(|d| matches!(d, 1 | 4 | 7 | 8)).count() as u64;
+    sum += outputs.iter().fold(0, |n, d| n * 10 + *d as u64);
+  }
+  Generated {
+    input,
+    answers: [easy, sum],
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// The `k`th of the 5040 orderings of the wires.
+  fn wiring(mut k: usize) -> Vec<u8> {
+    let mut wires = b"abcdefg".to_vec();
+    let mut wiring = Vec::new();
+    for left in (1..=7).rev() {
+      let rest = (1..left).product::<usize>();
+      wiring.push(wires.remove(k / rest));
+      k %= rest;
+    }
+    wiring
+  }
+
+  /// Decodes a display's output by trying every wiring until all ten patterns
+  /// are digits.
+  fn decode(line: &str) -> u64 {
+    let (patterns, outputs) = line.split_once(" | ").unwrap();
+    let digits = DIGITS.map(|d| d.bytes().collect::<Vec<_>>());
+    for wiring in (0..5040).map(wiring) {
+      let unwire = |s: &str| {
+        let mut segments = s
+          .bytes()
+          .map(|w| b'a' + wiring.iter().position(|x| *x == w).unwrap() as u8)
+          .collect::<Vec<_>>();
+        segments.sort_unstable();
+        digits.iter().position(|d| *d == segments)
+      };
+      if patterns.split(' ').all(|p| unwire(p).is_some()) {
+        return outputs.split(' ').fold(0, |n, o| n * 10 + unwire(o).unwrap() as u64);
+      }
+    }
+    panic!("No wiring fits {}", line);
+  }
+
+  #[test]
+  fn by_construction() {
+    let generated = generate(&mut Rng::new(3), 20);
+    let sum = generated.input.lines().map(decode).sum::<u64>();
+    assert_eq!(generated.answers[1], sum);
+
+    let easy = generated
+      .input
+      .lines()
+      .flat_map(|l| l.split(" | ").nth(1).unwrap().split(' '))
+      .filter(|o| matches!(o.len(), 2 | 3 | 4 | 7))
+      .count();
+    assert_eq!(generated.answers[0], easy as u64);
+  }
+}
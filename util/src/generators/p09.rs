@@ -0,0 +1,133 @@
+//! A heightmap of basins walled off by nines.
+//!
+//! Random centres are scattered over the map, every cell belongs to its
+//! nearest centre, cells bordering another centre's cells become walls, and
+//! the rest get deeper towards their centre.
+
+use {
+  super::{Generated, Rng},
+  std::{collections::VecDeque, fmt::Write},
+};
+
+struct Map {
+  width: usize,
+  height: usize,
+  cells: Vec<u8>,
+}
+
+impl Map {
+  fn neighbours(&self, i: usize) -> impl Iterator<Item = usize> {
+    let (x, y, width, height) = (i % self.width, i / self.width, self.width, self.height);
+    [
+      (x > 0).then(|| i - 1),
+      (x + 1 < width).then(|| i + 1),
+      (y > 0).then(|| i - width),
+      (y + 1 < height).then(|| i + width),
+    ]
+    .into_iter()
+    .flatten()
+  }
+}
+
+/// The risk level sum, and the product of the three largest basins' sizes.
+fn answers(map: &Map) -> [u64; 2] {
+  let low_points = (0..map.cells.len())
+    .filter(|i| map.neighbours(*i).all(|n| map.cells[n] > map.cells[*i]))
+    .collect::<Vec<_>>();
+  let risk = low_points.iter().map(|i| map.cells[*i] as u64 + 1).sum();
+
+  // Label each region of non-nines once; a low point's basin is the whole
+  // region it's in.
+  let mut region = vec![usize::MAX; map.cells.len()];
+  let mut region_sizes = Vec::new();
+  for start in 0..map.cells.len() {
+    if region[start] != usize::MAX || map.cells[start] == 9 {
+      continue;
+    }
+    let label = region_sizes.len();
+    let mut queue = VecDeque::from([start]);
+    region[start] = label;
+    let mut size = 0;
+    while let Some(i) = queue.pop_front() {
+      size += 1;
+      for n in map.neighbours(i) {
+        if region[n] == usize::MAX && map.cells[n] < 9 {
+          region[n] = label;
+          queue.push_back(n);
+        }
+      }
+    }
+    region_sizes.push(size);
+  }
+
+  let mut sizes = low_points.iter().map(|i| region_sizes[region[*i]]).collect::<Vec<u64>>();
+  sizes.sort_unstable_by(|a, b| b.cmp(a));
+  [risk, sizes.iter().take(3).product()]
+}
+
+pub(super) fn generate(rng: &mut Rng, size: usize) -> Generated {
+  let side = size.max(3);
+  // Roughly one basin per 50 cells, like the real input.
+  let centres = (0..(side * side / 50).max(3))
+    .map(|_| (rng.below(side as u64), rng.below(side as u64)))
+    .collect::<Vec<_>>();
+
+  let mut map = Map {
+    width: side,
+    height: side,
+    cells: vec![0; side * side],
+  };
+
+  // Flood outwards from every centre at once, so each cell is claimed by
+  // the nearest one.
+  let mut nearest: Vec<Option<(u64, usize)>> = vec![None; side * side];
+  let mut queue = VecDeque::new();
+  for (c, (x, y)) in centres.iter().enumerate() {
+    let i = *y as usize * side + *x as usize;
+    if nearest[i].is_none() {
+      nearest[i] = Some((0, c));
+      queue.push_back(i);
+    }
+  }
+  while let Some(i) = queue.pop_front() {
+    let (distance, centre) = nearest[i].unwrap();
+    for n in map.neighbours(i) {
+      if nearest[n].is_none() {
+        nearest[n] = Some((distance + 1, centre));
+        queue.push_back(n);
+      }
+    }
+  }
+  let nearest = nearest.into_iter().map(Option::unwrap).collect::<Vec<_>>();
+
+  for i in 0..side * side {
+    let (distance, centre) = nearest[i];
+    let border = map.neighbours(i).any(|n| nearest[n].1 != centre);
+    map.cells[i] = if border { 9 } else { distance.min(8) as u8 };
+  }
+
+  let mut input = String::new();
+  for row in map.cells.chunks(side) {
+    writeln!(input, "{}", row.iter().map(|h| (b'0' + h) as char).collect::<String>()).unwrap();
+  }
+  Generated {
+    answers: answers(&map),
+    input,
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn sample() {
+    let rows = ["2199943210", "3987894921", "9856789892", "8767896789", "9899965678"];
+    let map = Map {
+      width: 10,
+      height: 5,
+      cells: rows.iter().flat_map(|r| r.bytes().map(|b| b - b'0')).collect(),
+    };
+    assert_eq!(answers(&map), [15, 1134]);
+  }
+}
@@ -1,3 +1,10 @@
+pub mod answers;
+pub mod batch;
+pub mod error;
+pub mod generators;
+pub mod parse;
+pub mod trace;
+
 pub fn add(left: usize, right: usize) -> usize {
   left + right
 }
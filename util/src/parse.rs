@@ -0,0 +1,318 @@
+//! Parsers for the shapes of input that keep coming up: one value per line,
+//! comma-separated numbers, blocks separated by blank lines, grids of digits
+//! and lines of bits.
+//!
+//! Every error is a `Diagnostic` saying where in the input it happened, as
+//! `line L, column C: ...`, with both counted from one. The nom combinators are generic over the
+//! error type so they fit into parsers using either nom's default error or
+//! `VerboseError`.
+
+use {
+  crate::error::Diagnostic,
+  anyhow::Error,
+  nom::{
+    character::complete::{char, digit1, space0},
+    combinator::{consumed, eof, map_res, opt, recognize},
+    error::{context, FromExternalError, ParseError, VerboseError, VerboseErrorKind},
+    multi::separated_list1,
+    sequence::{delimited, pair, terminated},
+    IResult,
+  },
+  std::{fmt, str::FromStr},
+};
+
+/// Returns the line and column at which `part`, a slice of `full`, begins.
+pub fn position(full: &str, part: &str) -> (usize, usize) {
+  let offset = part.as_ptr() as usize - full.as_ptr() as usize;
+  assert!(offset <= full.len(), "not a slice of the input");
+  let before = &full[..offset];
+  let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+  (before.matches('\n').count() + 1, before[line_start..].chars().count() + 1)
+}
+
+/// An error pointing at where `part`, a slice of `full`, begins.
+pub fn error_at(full: &str, part: &str, message: impl fmt::Display) -> Error {
+  Diagnostic::at(full, part, message).into()
+}
+
+/// For parsers using nom's default error, which only knows where it failed,
+/// so the caller says what it was expecting there.
+pub fn nom_error(full: &str, err: nom::Err<nom::error::Error<&str>>, expected: &str) -> Error {
+  match err {
+    nom::Err::Error(e) | nom::Err::Failure(e) => error_at(full, e.input, format_args!("expected {}", expected)),
+    nom::Err::Incomplete(_) => error_at(full, &full[full.len()..], "unexpected end of input"),
+  }
+}
+
+/// For parsers using `VerboseError`, reporting the innermost thing we were
+/// trying to parse when it failed.
+pub fn verbose_error(full: &str, err: nom::Err<VerboseError<&str>>) -> Error {
+  match err {
+    nom::Err::Error(e) | nom::Err::Failure(e) => {
+      let (input, expected) = e
+        .errors
+        .iter()
+        .find_map(|(input, kind)| match kind {
+          VerboseErrorKind::Context(expected) => Some((*input, *expected)),
+          _ => None,
+        })
+        .unwrap_or((e.errors[0].0, "valid input"));
+      error_at(full, input, format_args!("expected {}", expected))
+    }
+    nom::Err::Incomplete(_) => error_at(full, &full[full.len()..], "unexpected end of input"),
+  }
+}
+
+/// A number without a sign.
+pub fn unsigned<'a, T, E>(i: &'a str) -> IResult<&'a str, T, E>
+where
+  T: FromStr,
+  E: ParseError<&'a str> + FromExternalError<&'a str, T::Err>,
+{
+  map_res(digit1, str::parse)(i)
+}
+
+/// A number, possibly negative.
+pub fn signed<'a, T, E>(i: &'a str) -> IResult<&'a str, T, E>
+where
+  T: FromStr,
+  E: ParseError<&'a str> + FromExternalError<&'a str, T::Err>,
+{
+  map_res(recognize(pair(opt(char('-')), digit1)), str::parse)(i)
+}
+
+/// Numbers separated by commas, with any spaces or tabs around the commas.
+pub fn comma_separated<'a, T, E>(i: &'a str) -> IResult<&'a str, Vec<T>, E>
+where
+  T: FromStr,
+  E: ParseError<&'a str> + FromExternalError<&'a str, T::Err>,
+{
+  separated_list1(delimited(space0, char(','), space0), signed)(i)
+}
+
+/// Parses `text`, which is the whole of line `line` of the input and starts
+/// `offset` bytes into it. This doesn't need the rest of the input, so it
+/// works on input that's read a line at a time.
+pub fn value<T>(line: usize, offset: usize, text: &str) -> Result<T, Error>
+where
+  T: FromStr,
+  T::Err: fmt::Display,
+{
+  text.parse().map_err(|e| {
+    Diagnostic {
+      file: None,
+      span: offset..offset + text.len(),
+      line,
+      column: 1,
+      message: format!("couldn't parse {:?}: {}", text, e),
+      source: text.to_owned(),
+    }
+    .into()
+  })
+}
+
+/// One value per line.
+pub fn values<T>(s: &str) -> Result<Vec<T>, Error>
+where
+  T: FromStr,
+  T::Err: fmt::Display,
+{
+  s.lines()
+    .enumerate()
+    .map(|(i, line)| value(i + 1, line.as_ptr() as usize - s.as_ptr() as usize, line))
+    .collect()
+}
+
+/// A single line of comma-separated numbers, which may be surrounded by
+/// whitespace.
+pub fn csv<T>(s: &str) -> Result<Vec<T>, Error>
+where
+  T: FromStr,
+{
+  Ok(csv_spanned(s)?.into_iter().map(|(_, n)| n).collect())
+}
+
+/// Like `csv`, but with the text of each number too, so checks on the values
+/// can point at the one that's wrong.
+pub fn csv_spanned<T>(s: &str) -> Result<Vec<(&str, T)>, Error>
+where
+  T: FromStr,
+{
+  let start = s.trim_start();
+  let (rest, nums) = separated_list1(
+    delimited(space0, char(','), space0),
+    consumed(signed::<T, nom::error::Error<&str>>),
+  )(start)
+  .map_err(|e| nom_error(s, e, "comma-separated numbers"))?;
+  if !rest.trim().is_empty() {
+    return Err(error_at(s, rest, "expected comma-separated numbers"));
+  }
+  Ok(nums)
+}
+
+/// Parses every line of `s` with `parser`, which must consume the whole line.
+pub fn each_line<'a, T>(
+  s: &'a str,
+  mut parser: impl FnMut(&'a str) -> IResult<&'a str, T, VerboseError<&'a str>>,
+) -> Result<Vec<T>, Error> {
+  s.lines()
+    .map(|line| {
+      terminated(&mut parser, context("end of line", eof))(line)
+        .map(|(_, val)| val)
+        .map_err(|e| verbose_error(s, e))
+    })
+    .collect()
+}
+
+/// The groups of lines separated by blank lines, as slices of `s` so errors
+/// within them can still be placed with `position`. A block doesn't include
+/// the newline ending its last line.
+pub fn blocks(s: &str) -> impl Iterator<Item = &str> {
+  let mut offset = 0;
+  let mut lines = s
+    .split_inclusive('\n')
+    .map(move |line| {
+      let start = offset;
+      offset += line.len();
+      let text = line.trim_end_matches(['\n', '\r']);
+      (start, start + text.len(), text.trim().is_empty())
+    })
+    .peekable();
+
+  std::iter::from_fn(move || {
+    let (start, mut end, _) = lines.find(|(_, _, blank)| !blank)?;
+    while let Some((_, line_end, _)) = lines.next_if(|(_, _, blank)| !blank) {
+      end = line_end;
+    }
+    Some(&s[start..end])
+  })
+}
+
+/// Lines of characters, all the same width, with `cell` turning each
+/// character into a value. `unit` names one character, e.g. "bit".
+fn rows<T>(
+  s: &str,
+  expected: &str,
+  unit: &str,
+  cell: impl Fn(char) -> Option<T>,
+) -> Result<Vec<Vec<T>>, Error> {
+  let mut rows: Vec<Vec<T>> = Vec::new();
+  for line in s.lines() {
+    let mut row = Vec::new();
+    for (i, c) in line.char_indices() {
+      row.push(cell(c).ok_or_else(|| error_at(s, &line[i..], format_args!("expected {}", expected)))?);
+    }
+    let width = rows.first().map_or(row.len(), Vec::len);
+    if width == 0 {
+      return Err(error_at(s, line, format_args!("expected at least one {}", unit)));
+    }
+    if row.len() != width {
+      let at = line.char_indices().nth(width).map_or(&line[line.len()..], |(i, _)| &line[i..]);
+      return Err(error_at(s, at, format_args!("expected {} {}s, found {}", width, unit, row.len())));
+    }
+    rows.push(row);
+  }
+  Ok(rows)
+}
+
+/// A rectangle of single digits, stored a row at a time.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct Grid {
+  pub width: usize,
+  pub height: usize,
+  pub cells: Vec<u8>,
+}
+
+/// One row of digits per line, with every row the same width.
+pub fn digit_grid(s: &str) -> Result<Grid, Error> {
+  let rows = rows(s, "a digit", "digit", |c| c.to_digit(10).map(|d| d as u8))?;
+  Ok(Grid {
+    width: rows.first().map_or(0, Vec::len),
+    height: rows.len(),
+    cells: rows.concat(),
+  })
+}
+
+/// One binary number per line, with every line the same width. Each line's
+/// bits are given most significant first, as written.
+pub fn binary(s: &str) -> Result<Vec<Vec<bool>>, Error> {
+  rows(s, "a binary digit", "bit", |c| match c {
+    '0' => Some(false),
+    '1' => Some(true),
+    _ => None,
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn numbers() {
+    assert_eq!(unsigned::<u32, ()>("12 x"), Ok((" x", 12)));
+    assert!(unsigned::<u32, ()>("-12").is_err());
+    assert_eq!(signed::<i64, ()>("-12,"), Ok((",", -12)));
+    assert!(signed::<u8, ()>("300").is_err());
+    assert_eq!(comma_separated::<u32, ()>("1, 2 ,3\n"), Ok(("\n", vec![1, 2, 3])));
+    assert_eq!(comma_separated::<u32, ()>("1,2,x"), Ok((",x", vec![1, 2])));
+  }
+
+  #[test]
+  fn lines() {
+    assert_eq!(values::<u16>("1\n2\n").unwrap(), vec![1, 2]);
+    assert_eq!(
+      values::<u16>("1\n70000\n").unwrap_err().to_string(),
+      "line 2, column 1: couldn't parse \"70000\": number too large to fit in target type"
+    );
+    let err = values::<u16>("1\n70000\n").unwrap_err();
+    assert_eq!(err.downcast_ref::<Diagnostic>().unwrap().span, 2..7);
+
+    assert_eq!(csv::<usize>(" 3,4,3,1,2\n").unwrap(), vec![3, 4, 3, 1, 2]);
+    assert_eq!(csv::<i32>("-1").unwrap(), vec![-1]);
+    assert_eq!(
+      csv::<usize>("3,4,x\n").unwrap_err().to_string(),
+      "line 1, column 4: expected comma-separated numbers"
+    );
+    assert_eq!(
+      csv::<usize>("\n").unwrap_err().to_string(),
+      "line 2, column 1: expected comma-separated numbers"
+    );
+    let spanned = csv_spanned::<u8>("1, 20,3").unwrap();
+    assert_eq!(spanned, vec![("1", 1), ("20", 20), ("3", 3)]);
+    assert_eq!(position("1, 20,3", spanned[1].0), (1, 4));
+
+    let pair = |i| nom::sequence::separated_pair(signed::<i32, _>, char(','), signed)(i);
+    assert_eq!(each_line("1,2\r\n3,4\n", pair).unwrap(), vec![(1, 2), (3, 4)]);
+    assert_eq!(
+      each_line("1,2\n3,4 \n", pair).unwrap_err().to_string(),
+      "line 2, column 4: expected end of line"
+    );
+  }
+
+  #[test]
+  fn blocks() {
+    let s = "\n1,2\n\n 1 2\r\n3 4 \n  \n\n5\n";
+    let found = super::blocks(s).collect::<Vec<_>>();
+    assert_eq!(found, vec!["1,2", " 1 2\r\n3 4 ", "5"]);
+    assert_eq!(position(s, found[2]), (8, 1));
+    assert_eq!(super::blocks("a\nb").collect::<Vec<_>>(), vec!["a\nb"]);
+    assert_eq!(super::blocks(" \n").count(), 0);
+  }
+
+  #[test]
+  fn grids() {
+    let grid = digit_grid("123\n456\n").unwrap();
+    assert_eq!((grid.width, grid.height), (3, 2));
+    assert_eq!(grid.cells, vec![1, 2, 3, 4, 5, 6]);
+    assert_eq!(digit_grid("").unwrap(), Grid::default());
+
+    let err = |s| digit_grid(s).unwrap_err().to_string();
+    assert_eq!(err("123\n4x6\n"), "line 2, column 2: expected a digit");
+    assert_eq!(err("123\n45\n"), "line 2, column 3: expected 3 digits, found 2");
+    assert_eq!(err("123\n4567\n"), "line 2, column 4: expected 3 digits, found 4");
+
+    assert_eq!(binary("10\n01\n").unwrap(), vec![vec![true, false], vec![false, true]]);
+    assert_eq!(binary("\n").unwrap_err().to_string(), "line 1, column 1: expected at least one bit");
+    assert_eq!(binary("10\n12\n").unwrap_err().to_string(), "line 2, column 2: expected a binary digit");
+  }
+}
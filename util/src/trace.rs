@@ -0,0 +1,65 @@
+//! Where the solvers' `tracing` spans and events go.
+//!
+//! Each part of a solve runs in a span (`parse`, `part1`, `part2`), and the
+//! interesting steps inside are events, e.g. a bingo board winning. Nothing
+//! below a warning is shown unless asked for, either with `-v` (info), `-vv`
+//! (debug) or `-vvv` (trace), or with a filter in `AOC_LOG`, like
+//! `AOC_LOG=p04_giant_squid=debug`, which takes precedence over `-v`.
+
+use {
+  anyhow::{anyhow, Error},
+  std::{
+    env,
+    io::{self, IsTerminal},
+  },
+  tracing_subscriber::EnvFilter,
+};
+
+/// The environment variable holding a filter, in `tracing_subscriber`'s
+/// `EnvFilter` syntax.
+pub const ENV_VAR: &str = "AOC_LOG";
+
+/// The filter to use given how many times `-v` was passed and what's in
+/// `AOC_LOG`, if anything.
+pub fn filter(verbosity: u8, env: Option<&str>) -> Result<EnvFilter, Error> {
+  match env.filter(|s| !s.trim().is_empty()) {
+    Some(directives) => {
+      EnvFilter::try_new(directives).map_err(|e| anyhow!("Invalid {} {:?}: {}", ENV_VAR, directives, e))
+    }
+    None => Ok(EnvFilter::new(match verbosity {
+      0 => "warn",
+      1 => "info",
+      2 => "debug",
+      _ => "trace",
+    })),
+  }
+}
+
+/// Sends events to stderr, so they don't get mixed up with the answers.
+pub fn init(verbosity: u8) -> Result<(), Error> {
+  let env = env::var(ENV_VAR).ok();
+  tracing_subscriber::fmt()
+    .with_env_filter(filter(verbosity, env.as_deref())?)
+    .with_writer(io::stderr)
+    .with_ansi(io::stderr().is_terminal())
+    .without_time()
+    .try_init()
+    .map_err(|e| anyhow!(e))
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn filters() {
+    assert_eq!(filter(0, None).unwrap().to_string(), "warn");
+    assert_eq!(filter(2, None).unwrap().to_string(), "debug");
+    assert_eq!(filter(9, Some("")).unwrap().to_string(), "trace");
+    assert_eq!(
+      filter(1, Some("p04_giant_squid=debug")).unwrap().to_string(),
+      "p04_giant_squid=debug"
+    );
+    assert!(filter(0, Some("p04=loud")).is_err());
+  }
+}